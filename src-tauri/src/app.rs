@@ -0,0 +1,117 @@
+use std::sync::Mutex;
+
+use crate::export::{self, ExportFormat};
+use crate::history::{Entry, History, Page};
+use crate::import::{self, ImportReport};
+use crate::parser::{self, calculate::Implicit, constants::{Constant, Target}, context::{AngleUnit, Context}, format::{FormatOptions, Formatted, Representations}, numeric::Sample, session::{Session, Statement}};
+use crate::settings::{Settings, Store};
+
+/**
+ Entries list_history gives when no limit is asked for.
+ */
+const HISTORY_PAGE: usize = 50;
+
+/**
+ The settings a command falls back on for what it is not handed.
+ */
+fn settings(store: &tauri::State<'_, Mutex<Store>>) -> Result<Settings, String> {
+    store.lock().map(|store| store.get().clone()).map_err(|_| "The settings are not available".to_string())
+}
+
+#[tauri::command]
+async fn calculate(store: tauri::State<'_, Mutex<Store>>, expression: &str, target: Option<Target>, modulus: Option<i64>, implicit: Option<Implicit>, format: Option<FormatOptions>, angle: Option<AngleUnit>) -> Result<Formatted, String> {
+    let settings = settings(&store)?;
+    let mut parser = parser::calculate::Parser::with_implicit(expression, implicit.unwrap_or(settings.implicit));
+    let constant = parser.execute_in(target.unwrap_or(settings.target), &Context { modulus, angle: angle.unwrap_or(settings.angle), ..Context::default() })?;
+    Ok(Formatted { constant, options: format.unwrap_or(settings.format) })
+}
+
+#[tauri::command]
+async fn representations(store: tauri::State<'_, Mutex<Store>>, expression: &str, modulus: Option<i64>, implicit: Option<Implicit>, format: Option<FormatOptions>) -> Result<Representations, String> {
+    let settings = settings(&store)?;
+    let mut parser = parser::calculate::Parser::with_implicit(expression, implicit.unwrap_or(settings.implicit));
+    let constant = parser.evaluate_in(&Context { modulus, angle: settings.angle, ..Context::default() })?;
+    Representations::new(constant, &format.unwrap_or(settings.format))
+}
+
+#[tauri::command]
+async fn run_script(store: tauri::State<'_, Mutex<Store>>, script: &str, target: Option<Target>, modulus: Option<i64>, implicit: Option<Implicit>) -> Result<Vec<Statement>, String> {
+    let settings = settings(&store)?;
+    let mut session = Session::new(Context { modulus, angle: settings.angle, ..Context::default() });
+    session.implicit = implicit.unwrap_or(settings.implicit);
+    Ok(session.run_script(script, target.unwrap_or(settings.target)))
+}
+
+#[tauri::command]
+async fn sample(store: tauri::State<'_, Mutex<Store>>, expression: &str, variable: &str, from: f64, to: f64, n: usize, implicit: Option<Implicit>) -> Result<Sample, String> {
+    let settings = settings(&store)?;
+    let expression = parser::calculate::Parser::with_implicit(expression, implicit.unwrap_or(settings.implicit)).parse()?;
+    parser::numeric::sample(&expression, variable, from, to, n, &Context { angle: settings.angle, ..Context::default() })
+}
+
+#[tauri::command]
+async fn get_settings(store: tauri::State<'_, Mutex<Store>>) -> Result<Settings, String> {
+    settings(&store)
+}
+
+#[tauri::command]
+async fn set_settings(store: tauri::State<'_, Mutex<Store>>, settings: Settings) -> Result<Settings, String> {
+    let mut store = store.lock().map_err(|_| "The settings are not available".to_string())?;
+    store.set(settings)?;
+    Ok(store.get().clone())
+}
+
+#[tauri::command]
+async fn append_history(history: tauri::State<'_, Mutex<History>>, expression: String, target: Target, result: Constant) -> Result<Entry, String> {
+    history.lock().map_err(|_| "The history is not available".to_string())?.append(expression, target, result)
+}
+
+#[tauri::command]
+async fn list_history(history: tauri::State<'_, Mutex<History>>, offset: Option<usize>, limit: Option<usize>) -> Result<Page, String> {
+    let history = history.lock().map_err(|_| "The history is not available".to_string())?;
+    Ok(history.list(offset.unwrap_or(0), limit.unwrap_or(HISTORY_PAGE)))
+}
+
+#[tauri::command]
+async fn delete_history(history: tauri::State<'_, Mutex<History>>, ids: Vec<u64>) -> Result<usize, String> {
+    history.lock().map_err(|_| "The history is not available".to_string())?.delete(&ids)
+}
+
+#[tauri::command]
+async fn clear_history(history: tauri::State<'_, Mutex<History>>) -> Result<(), String> {
+    history.lock().map_err(|_| "The history is not available".to_string())?.clear()
+}
+
+#[tauri::command]
+async fn export_history(history: tauri::State<'_, Mutex<History>>, format: ExportFormat, path: Option<String>) -> Result<String, String> {
+    let history = history.lock().map_err(|_| "The history is not available".to_string())?;
+    let exported = export::export(history.entries(), format)?;
+    if let Some(path) = path {
+        std::fs::write(&path, &exported).map_err(|err| format!("Could not write {}: {}", path, err))?;
+    }
+    Ok(exported)
+}
+
+#[tauri::command]
+async fn import_history(history: tauri::State<'_, Mutex<History>>, contents: String) -> Result<ImportReport, String> {
+    import::import(&mut *history.lock().map_err(|_| "The history is not available".to_string())?, &contents)
+}
+
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    tauri::Builder::default()
+        .setup(|app| {
+            use tauri::Manager;
+            let path = app.path().app_data_dir()?.join("history.jsonl");
+            app.manage(Mutex::new(History::open(path)?));
+            let path = app.path().app_config_dir()?.join("settings.json");
+            app.manage(Mutex::new(Store::open(path)));
+            Ok(())
+        })
+        .invoke_handler(tauri::generate_handler![
+            calculate, representations, run_script, sample, get_settings, set_settings,
+            append_history, list_history, delete_history, clear_history, export_history, import_history
+        ])
+        .run(tauri::generate_context!())
+        .expect("error while running tauri application");
+}
@@ -0,0 +1,224 @@
+use std::{env, io::{self, BufRead}, path::PathBuf, process::ExitCode};
+
+use calcit::{logger::logger, parser::{calculate::Implicit, constants::{Constant, Target}, context::{AngleUnit, Context}, session::{Session, Statement}}, settings::{Settings, Store}};
+use is_terminal::IsTerminal;
+use serde::Serialize;
+
+mod repl;
+
+const USAGE: &str = "Usage: calcit-cli [options] [expression ...]
+
+Runs every expression in order and prints its result, a line per result.
+Without expressions the lines of stdin are run instead, so calcit-cli can be piped into.
+When stdin is a terminal an interactive session starts instead, type :help in it for its commands.
+Variables and functions an expression defines are kept for the ones after it.
+The target, angle mode, implicit multiplication and format are the ones set in the app,
+kept in the settings.json of its configuration directory or in the file CALCIT_SETTINGS names.
+
+Options:
+  --int, --float, --bin, --hex, --oct, --bool   Turns every result into the target
+                                                without one a float stays a float and the rest takes the set target
+  --deg, --rad, --grad                          The angle mode
+  --implicit <textbook|strict>                  How 2x binds, textbook binds it tighter than * and /
+  --mod <n>                                     Works modulo n
+  -i, --interactive                             Starts the interactive session even when stdin is not a terminal
+  -h, --help                                    Shows this help
+
+A failing expression is written to stderr as a line of JSON, {\"line\":1,\"input\":\"1 +\",\"error\":\"...\"},
+the ones after it still run and calcit-cli exits with 1. Invalid options exit with 2.";
+
+/**
+ The names targets go by, as options and as commands of the interactive session.
+ */
+const TARGETS: [(&str, Target); 6] = [
+    ("int", Target::Integer),
+    ("float", Target::Float),
+    ("bin", Target::Binary),
+    ("hex", Target::Hexadecimal),
+    ("oct", Target::Octal),
+    ("bool", Target::Boolean),
+];
+
+/**
+ The names angle modes go by, as options and as commands of the interactive session.
+ */
+const ANGLES: [(&str, AngleUnit); 3] = [
+    ("deg", AngleUnit::Degrees),
+    ("rad", AngleUnit::Radians),
+    ("grad", AngleUnit::Gradians),
+];
+
+/**
+ The names implicit multiplications go by, as options and as commands of the interactive session.
+ */
+const IMPLICITS: [(&str, Implicit); 2] = [
+    ("textbook", Implicit::Textbook),
+    ("strict", Implicit::Strict),
+];
+
+/**
+ The identifier of the app in tauri.conf.json, its configuration directory is named after it.
+ */
+const IDENTIFIER: &str = "com.calcit.dev";
+
+/**
+ What the command line asks for, the settings with the angle mode and implicit multiplication of the options in them.
+ */
+struct Options {
+    target: Option<Target>,
+    modulus: Option<i64>,
+    settings: Settings,
+    expressions: Vec<String>,
+    interactive: bool,
+}
+
+/**
+ An expression that failed, written to stderr so scripts can read it.
+ * The line is the line of stdin or the position of the argument, counting from 1
+ */
+#[derive(Serialize)]
+struct Failure<'a> {
+    line: usize,
+    input: &'a str,
+    error: &'a str,
+}
+
+fn main() -> ExitCode {
+    //the log of the parser would end up between the results
+    logger::silence();
+    let settings = path().map_or_else(Settings::default, |path| Store::open(path).get().clone());
+    let options = match options(env::args().skip(1), settings) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        },
+        Err(err) => {
+            eprintln!("calcit-cli: {}\n\n{}", err, USAGE);
+            return ExitCode::from(2);
+        },
+    };
+    let mut session = Session::new(Context { modulus: options.modulus, angle: options.settings.angle, ..Context::default() });
+    session.implicit = options.settings.implicit;
+    if options.expressions.is_empty() && (options.interactive || io::stdin().is_terminal()) {
+        return repl::run(&mut session, options.target, &options.settings);
+    }
+    let mut failed = false;
+    if options.expressions.is_empty() {
+        for (i, line) in io::stdin().lock().lines().enumerate() {
+            let line = match line {
+                Ok(line) => line,
+                Err(err) => {
+                    eprintln!("calcit-cli: Could not read stdin: {}", err);
+                    return ExitCode::from(2);
+                },
+            };
+            for statement in session.run_script(&line, None) {
+                failed |= !print(i + 1, statement, &options);
+            }
+        }
+    } else {
+        for (i, expression) in options.expressions.iter().enumerate() {
+            for statement in session.run_script(expression, None) {
+                failed |= !print(i + 1, statement, &options);
+            }
+        }
+    }
+    if failed { ExitCode::FAILURE } else { ExitCode::SUCCESS }
+}
+
+/**
+ Reads the arguments on top of the settings, None when the help is asked for.
+ */
+fn options(mut arguments: impl Iterator<Item = String>, settings: Settings) -> Result<Option<Options>, String> {
+    let mut options = Options { target: None, modulus: None, settings, expressions: Vec::new(), interactive: false };
+    while let Some(argument) = arguments.next() {
+        if let Some(target) = argument.strip_prefix("--").and_then(target) {
+            options.target = Some(target);
+            continue;
+        }
+        if let Some(angle) = argument.strip_prefix("--").and_then(angle) {
+            options.settings.angle = angle;
+            continue;
+        }
+        match argument.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-i" | "--interactive" => options.interactive = true,
+            "--mod" => options.modulus = Some(modulus(&arguments.next().ok_or("--mod needs a modulus")?)?),
+            "--implicit" => options.settings.implicit = implicit(&arguments.next().ok_or("--implicit needs textbook or strict")?)?,
+            //everything after -- is an expression, even when it starts with a dash
+            "--" => options.expressions.extend(arguments.by_ref()),
+            //a negative number is an expression rather than an option
+            s if s.starts_with("--") || (s.starts_with('-') && !s[1..].starts_with(|c: char| c.is_ascii_digit() || c == '.' || c == '(')) => {
+                return Err(format!("Unknown option {}", s));
+            },
+            _ => options.expressions.push(argument),
+        }
+    }
+    Ok(Some(options))
+}
+
+fn target(name: &str) -> Option<Target> {
+    TARGETS.iter().find(|(n, _)| *n == name).map(|(_, target)| *target)
+}
+
+fn angle(name: &str) -> Option<AngleUnit> {
+    ANGLES.iter().find(|(n, _)| *n == name).map(|(_, angle)| *angle)
+}
+
+fn implicit(name: &str) -> Result<Implicit, String> {
+    IMPLICITS.iter().find(|(n, _)| *n == name).map(|(_, implicit)| *implicit).ok_or_else(|| format!("{} is not textbook or strict", name))
+}
+
+fn modulus(s: &str) -> Result<i64, String> {
+    s.parse::<i64>().map_err(|_| format!("{} is not a valid modulus", s))
+}
+
+/**
+ The settings file of the app, None when there is no configuration directory to find it in.
+ * The directory is the one tauri gives the app, CALCIT_SETTINGS names another file
+ */
+fn path() -> Option<PathBuf> {
+    if let Some(path) = env::var_os("CALCIT_SETTINGS") {
+        return Some(PathBuf::from(path));
+    }
+    let home = || env::var_os("HOME").map(PathBuf::from);
+    let directory = if cfg!(windows) {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        home().map(|home| home.join("Library").join("Application Support"))
+    } else {
+        env::var_os("XDG_CONFIG_HOME").map(PathBuf::from).filter(|path| path.is_absolute()).or_else(|| home().map(|home| home.join(".config")))
+    };
+    directory.map(|directory| directory.join(IDENTIFIER).join("settings.json"))
+}
+
+/**
+ A result the way it is shown, in the target when one is asked for.
+ * Without one a float stays a float rather than being read as the bits of an integer and everything else takes the set target
+ */
+fn shown(constant: Constant, target: Option<Target>, settings: &Settings) -> Constant {
+    match (target, constant) {
+        (Some(target), constant) => constant.into_target_constant(target),
+        (None, Constant::List(items)) => Constant::List(items.into_iter().map(|item| shown(item, None, settings)).collect()),
+        (None, constant @ Constant::Float(_)) => constant,
+        (None, constant) => constant.into_target_constant(settings.target),
+    }
+}
+
+/**
+ Writes the result of a statement to stdout or its error to stderr, gives whether it succeeded.
+ */
+fn print(line: usize, statement: Statement, options: &Options) -> bool {
+    match statement.result {
+        Ok(constant) => {
+            println!("{}", shown(constant, options.target, &options.settings).describe(&options.settings.format).1);
+            true
+        },
+        Err(error) => {
+            let failure = Failure { line, input: &statement.input, error: &error };
+            eprintln!("{}", serde_json::to_string(&failure).unwrap_or(error));
+            false
+        },
+    }
+}
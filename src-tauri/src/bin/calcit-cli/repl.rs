@@ -0,0 +1,200 @@
+use std::{env, ops::Range, path::PathBuf, process::ExitCode};
+
+use calcit::{parser::{constants::Target, context::AngleUnit, session::Session, tokens::Token}, settings::Settings};
+use logos::Logos;
+use rustyline::{completion::Completer, error::ReadlineError, highlight::Highlighter, hint::Hinter, history::DefaultHistory, validate::{ValidationContext, ValidationResult, Validator}, Editor, Helper};
+
+use super::{angle, implicit, modulus, shown, ANGLES, TARGETS};
+
+const HELP: &str = "Type an expression to run it, name = expression and name(x) = expression define variables and functions.
+An expression goes on over more lines while a bracket is open.
+
+Commands:
+  :int, :float, :bin, :hex, :oct, :bool   Turns every result into the target
+  :auto                                   Goes back to floats staying floats and the rest taking the set target
+  :deg, :rad, :grad                       Switches the angle mode
+  :implicit <textbook|strict>             Switches how 2x binds
+  :mod <n>                                Works modulo n, :mod alone stops
+  :vars                                   Lists the variables and functions
+  :reset                                  Forgets the variables and functions
+  :help                                   Shows this help
+  :quit                                   Leaves, as does Ctrl-D";
+
+const RED: &str = "\x1b[31m";
+const RESET: &str = "\x1b[0m";
+
+/**
+ Keeps rustyline reading lines while a bracket is still open.
+ */
+struct Input;
+
+impl Validator for Input {
+    fn validate(&self, ctx: &mut ValidationContext) -> rustyline::Result<ValidationResult> {
+        Ok(if Session::is_complete(ctx.input()) { ValidationResult::Valid(None) } else { ValidationResult::Incomplete })
+    }
+}
+
+impl Completer for Input {
+    type Candidate = String;
+}
+
+impl Hinter for Input {
+    type Hint = String;
+}
+
+impl Highlighter for Input {}
+
+impl Helper for Input {}
+
+/**
+ Reads and runs what is typed until the input ends, in the same session the command line uses.
+ * The lines are kept in ~/.calcit_history, or in the file CALCIT_HISTORY names, for the next time
+ */
+pub fn run(session: &mut Session, mut target: Option<Target>, settings: &Settings) -> ExitCode {
+    let mut editor = match Editor::<Input, DefaultHistory>::new() {
+        Ok(editor) => editor,
+        Err(err) => {
+            eprintln!("calcit-cli: Could not start the interactive session: {}", err);
+            return ExitCode::from(2);
+        },
+    };
+    editor.set_helper(Some(Input));
+    let path = history();
+    if let Some(path) = &path {
+        //there is no file yet the first time
+        let _ = editor.load_history(path);
+    }
+    loop {
+        let input = match editor.readline(&prompt(session, target)) {
+            Ok(input) => input,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(err) => {
+                eprintln!("calcit-cli: {}", err);
+                break;
+            },
+        };
+        if input.trim().is_empty() {
+            continue;
+        }
+        let _ = editor.add_history_entry(input.as_str());
+        match input.trim() {
+            ":quit" | ":q" | ":exit" => break,
+            s if s.starts_with(':') => {
+                if let Err(err) = command(session, &mut target, settings, &s[1..]) {
+                    eprintln!("{}{}{}", RED, err, RESET);
+                }
+            },
+            _ => for statement in session.run_script(&input, None) {
+                match statement.result {
+                    Ok(constant) => println!("{}", shown(constant, target, settings).describe(&settings.format).1),
+                    Err(err) => error(&statement.input, &err),
+                }
+            },
+        }
+    }
+    if let Some(path) = &path {
+        if let Err(err) = editor.save_history(path) {
+            eprintln!("calcit-cli: Could not save the history to {}: {}", path.display(), err);
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+/**
+ The target and, when it is not radians, the angle mode, int> or float deg>, calcit> when no target is asked for.
+ */
+fn prompt(session: &Session, target: Option<Target>) -> String {
+    let target = match target {
+        Some(target) => TARGETS.iter().find(|(_, t)| *t == target).map_or("", |(name, _)| name),
+        None => "calcit",
+    };
+    match ANGLES.iter().find(|(_, angle)| *angle == session.context.angle && *angle != AngleUnit::Radians) {
+        Some((angle, _)) => format!("{} {}> ", target, angle),
+        None => format!("{}> ", target),
+    }
+}
+
+/**
+ Runs a command, the text after the colon.
+ */
+fn command(session: &mut Session, target: &mut Option<Target>, settings: &Settings, command: &str) -> Result<(), String> {
+    let mut words = command.split_whitespace();
+    let name = words.next().unwrap_or_default();
+    if let Some(t) = super::target(name) {
+        *target = Some(t);
+        return Ok(());
+    }
+    if let Some(angle) = angle(name) {
+        session.context.angle = angle;
+        return Ok(());
+    }
+    match name {
+        "auto" => *target = None,
+        "implicit" => session.implicit = implicit(words.next().ok_or(":implicit needs textbook or strict")?)?,
+        "mod" => session.context.modulus = words.next().map(modulus).transpose()?,
+        "vars" => {
+            let mut variables: Vec<_> = session.context.variables.iter().collect();
+            if variables.is_empty() {
+                println!("No variables yet");
+            }
+            variables.sort_by(|a, b| a.0.cmp(b.0));
+            for (name, value) in variables {
+                println!("{} = {}", name, value.describe(&settings.format).1);
+            }
+        },
+        "reset" => session.context.variables.clear(),
+        "help" => println!("{}", HELP),
+        _ => return Err(format!("Unknown command :{}, :help lists the commands", name)),
+    }
+    Ok(())
+}
+
+/**
+ Shows an error in red, with carets under the part of the statement it is about when that can be found.
+ */
+fn error(input: &str, message: &str) {
+    let span = match locate(input, message) {
+        Some(span) => span,
+        None => {
+            eprintln!("{}{}{}", RED, message, RESET);
+            return;
+        },
+    };
+    //only the line of a statement over more lines that the error is on
+    let start = input[..span.start].rfind('\n').map_or(0, |i| i + 1);
+    let end = input[span.start..].find('\n').map_or(input.len(), |i| span.start + i);
+    let indent = input[start..span.start].chars().count();
+    let width = input[span.start..span.end.min(end)].chars().count().max(1);
+    eprintln!("  {}", &input[start..end]);
+    eprintln!("  {}{}{} {}{}", " ".repeat(indent), RED, "^".repeat(width), message, RESET);
+}
+
+/**
+ Where in the statement an error is, a character that can not be read or the name an error is about.
+ */
+fn locate(input: &str, message: &str) -> Option<Range<usize>> {
+    let mut lexer = Token::lexer(input);
+    let mut named = None;
+    while let Some(token) = lexer.next() {
+        match token {
+            Err(_) => return Some(lexer.span()),
+            //Unknown variable: x, or asin is not defined for 2
+            Ok(Token::Identifier(name)) if named.is_none() && (message.ends_with(&format!(": {}", name)) || message.starts_with(&format!("{} ", name))) => {
+                named = Some(lexer.span());
+            },
+            _ => {},
+        }
+    }
+    named
+}
+
+/**
+ The file the typed lines are kept in, None when there is no home directory to put it in.
+ */
+fn history() -> Option<PathBuf> {
+    if let Some(path) = env::var_os("CALCIT_HISTORY") {
+        return Some(PathBuf::from(path));
+    }
+    env::var_os("HOME").or_else(|| env::var_os("USERPROFILE")).map(|home| PathBuf::from(home).join(".calcit_history"))
+}
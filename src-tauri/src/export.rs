@@ -0,0 +1,124 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{history::Entry, import, parser::format::FormatOptions};
+
+/**
+ The kinds of file the history can be exported to.
+ */
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+    Markdown,
+    JsonLines,
+    /**
+     A printable page that looks like the paper tape of an adding machine
+     */
+    Html,
+}
+
+/**
+ Writes the entries out, oldest first.
+ * Every format but JSON Lines shows the result as text in the target it was computed in, 0xff for a hexadecimal result
+ * JSON Lines keeps the entries as they are stored so they can be imported again, after a line with the version of the file
+ */
+pub fn export(entries: &[Entry], format: ExportFormat) -> Result<String, String> {
+    match format {
+        ExportFormat::Csv => {
+            let mut s = String::from("id,time,expression,target,result\n");
+            for entry in entries {
+                let row = [entry.id.to_string(), time(entry.timestamp), entry.expression.clone(), target(entry), result(entry)];
+                s.push_str(&row.iter().map(|field| csv(field)).collect::<Vec<String>>().join(","));
+                s.push('\n');
+            }
+            Ok(s)
+        },
+        ExportFormat::Markdown => {
+            let mut s = String::from("| Time | Expression | Target | Result |\n| --- | --- | --- | --- |\n");
+            for entry in entries {
+                s.push_str(&format!("| {} | `{}` | {} | `{}` |\n", time(entry.timestamp), markdown(&entry.expression), target(entry), markdown(&result(entry))));
+            }
+            Ok(s)
+        },
+        ExportFormat::JsonLines => {
+            let mut s = format!("{{\"version\":{}}}\n", import::VERSION);
+            for entry in entries {
+                s.push_str(&serde_json::to_string(entry).map_err(|err| err.to_string())?);
+                s.push('\n');
+            }
+            Ok(s)
+        },
+        ExportFormat::Html => {
+            let mut s = String::from(TAPE_HEAD);
+            for entry in entries {
+                s.push_str(&format!(
+                    "<div class=\"entry\">\n<div class=\"meta\">{} &middot; {}</div>\n<div class=\"expression\">{}</div>\n<div class=\"result\">= {}</div>\n</div>\n",
+                    time(entry.timestamp), target(entry), html(&entry.expression), html(&result(entry)),
+                ));
+            }
+            s.push_str("</body>\n</html>\n");
+            Ok(s)
+        },
+    }
+}
+
+const TAPE_HEAD: &str = "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>CalcIt tape</title>
+<style>
+body { font-family: monospace; width: 24em; margin: 2em auto; }
+.entry { border-bottom: 1px dashed #999; padding: 0.5em 0; page-break-inside: avoid; }
+.meta { color: #777; font-size: 0.8em; }
+.result { text-align: right; font-weight: bold; }
+@media print { body { margin: 0; } }
+</style>
+</head>
+<body>
+";
+
+fn target(entry: &Entry) -> String {
+    format!("{:?}", entry.target)
+}
+
+fn result(entry: &Entry) -> String {
+    entry.result.describe(&FormatOptions::default()).1
+}
+
+/**
+ The timestamp as a UTC date and time, 2023-06-01 12:00:00.
+ */
+fn time(timestamp: u64) -> String {
+    let seconds = timestamp / 1000;
+    let (days, rest) = ((seconds / 86400) as i64, seconds % 86400);
+    //days since 1970-01-01 to a civil date, counting in eras of 400 years that start on March 1st
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02}", year, month, day, rest / 3600, rest / 60 % 60, rest % 60)
+}
+
+/**
+ Quotes a CSV field when it holds a comma, a quote or a line break.
+ */
+fn csv(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn markdown(s: &str) -> String {
+    s.replace('|', "\\|").replace('\n', " ")
+}
+
+fn html(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
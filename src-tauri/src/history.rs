@@ -0,0 +1,146 @@
+use std::{fs::{self, File, OpenOptions}, io::{BufRead, BufReader, Write}, path::PathBuf, time::{SystemTime, UNIX_EPOCH}};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{logger::logger, parser::constants::{Constant, Target}};
+
+/**
+ One calculation kept in the history, the timestamp counts milliseconds since the unix epoch.
+ */
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub id: u64,
+    pub expression: String,
+    pub target: Target,
+    pub result: Constant,
+    pub timestamp: u64,
+}
+
+/**
+ A line of the history file, the file is only appended to until it is cleared.
+ */
+#[derive(Deserialize, Serialize)]
+enum Record {
+    Append(Entry),
+    Delete(Vec<u64>),
+}
+
+/**
+ Part of the history, newest first, along with how many entries there are in total.
+ */
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Page {
+    pub entries: Vec<Entry>,
+    pub total: usize,
+}
+
+/**
+ The history of calculations, kept in memory and in a JSON Lines file that survives a restart.
+ */
+pub struct History {
+    path: PathBuf,
+    entries: Vec<Entry>,
+    next: u64,
+}
+
+impl History {
+    /**
+     Opens the history file, creating it and its directory when they do not exist yet.
+     * The file is replayed line by line, a line that can not be read, like one cut off by a crash, is skipped
+     */
+    pub fn open(path: PathBuf) -> Result<Self, String> {
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory).map_err(|err| format!("Could not create {}: {}", directory.display(), err))?;
+        }
+        let mut entries: Vec<Entry> = Vec::new();
+        let mut next = 0;
+        if path.exists() {
+            let file = File::open(&path).map_err(|err| format!("Could not open {}: {}", path.display(), err))?;
+            for (i, line) in BufReader::new(file).lines().enumerate() {
+                let line = line.map_err(|err| format!("Could not read {}: {}", path.display(), err))?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                match serde_json::from_str::<Record>(&line) {
+                    Ok(Record::Append(entry)) => {
+                        next = next.max(entry.id + 1);
+                        entries.push(entry);
+                    },
+                    Ok(Record::Delete(ids)) => entries.retain(|entry| !ids.contains(&entry.id)),
+                    Err(err) => {
+                        logger::error!("Skipping line {} of the history: {}", i + 1, err);
+                    },
+                }
+            }
+        }
+        Ok(Self { path, entries, next })
+    }
+
+    /**
+     Adds a calculation to the end of the history.
+     */
+    pub fn append(&mut self, expression: String, target: Target, result: Constant) -> Result<Entry, String> {
+        self.insert(expression, target, result, None)
+    }
+
+    /**
+     Adds a calculation made at another time, like one that is imported, now when no time is given.
+     */
+    pub fn insert(&mut self, expression: String, target: Target, result: Constant, timestamp: Option<u64>) -> Result<Entry, String> {
+        let timestamp = timestamp.unwrap_or_else(|| {
+            SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_millis() as u64).unwrap_or(0)
+        });
+        let entry = Entry { id: self.next, expression, target, result, timestamp };
+        self.write(&Record::Append(entry.clone()))?;
+        self.next += 1;
+        self.entries.push(entry.clone());
+        Ok(entry)
+    }
+
+    /**
+     At most limit entries, newest first, after skipping the offset newest ones.
+     */
+    pub fn list(&self, offset: usize, limit: usize) -> Page {
+        Page {
+            entries: self.entries.iter().rev().skip(offset).take(limit).cloned().collect(),
+            total: self.entries.len(),
+        }
+    }
+
+    /**
+     Every entry, oldest first.
+     */
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /**
+     Removes the entries with these ids, ids that are not in the history are ignored.
+     * Gives how many entries were removed
+     */
+    pub fn delete(&mut self, ids: &[u64]) -> Result<usize, String> {
+        let ids: Vec<u64> = ids.iter().copied().filter(|id| self.entries.iter().any(|entry| entry.id == *id)).collect();
+        if ids.is_empty() {
+            return Ok(0);
+        }
+        self.write(&Record::Delete(ids.clone()))?;
+        self.entries.retain(|entry| !ids.contains(&entry.id));
+        Ok(ids.len())
+    }
+
+    /**
+     Removes every entry, the file is emptied rather than appended to.
+     */
+    pub fn clear(&mut self) -> Result<(), String> {
+        File::create(&self.path).map_err(|err| format!("Could not clear {}: {}", self.path.display(), err))?;
+        self.entries.clear();
+        Ok(())
+    }
+
+    fn write(&self, record: &Record) -> Result<(), String> {
+        let line = serde_json::to_string(record).map_err(|err| err.to_string())?;
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)
+            .map_err(|err| format!("Could not open {}: {}", self.path.display(), err))?;
+        writeln!(file, "{}", line).map_err(|err| format!("Could not write to {}: {}", self.path.display(), err))
+    }
+}
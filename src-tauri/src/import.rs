@@ -0,0 +1,140 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{history::History, parser::{calculate::Parser, constants::{Constant, Target}}};
+
+/**
+ The newest history file version, written in the first line of a JSON Lines export as {"version": 1}.
+ * Version 0 is the results.json of older releases, an object from each expression to its result
+ * Version 1 is an entry per line, a file without the version line is read as version 1 too
+ */
+pub const VERSION: u64 = 1;
+
+/**
+ An entry of an imported file, the id is left out since imported entries get new ones.
+ * An entry without a timestamp is stamped with the time of the import
+ */
+#[derive(Deserialize)]
+struct Imported {
+    expression: String,
+    target: Target,
+    result: Constant,
+    timestamp: Option<u64>,
+}
+
+/**
+ The entries of a file along with the line each one is on.
+ */
+type Lines = Vec<(usize, Result<Imported, String>)>;
+
+/**
+ A line that could not be imported and why, the rest of the file is imported anyway.
+ */
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ImportError {
+    pub line: usize,
+    pub message: String,
+}
+
+/**
+ What an import did, entries that are already in the history are counted as duplicates and left out.
+ */
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ImportReport {
+    pub version: u64,
+    pub imported: usize,
+    pub duplicates: usize,
+    pub errors: Vec<ImportError>,
+}
+
+/**
+ Checks every entry of the file and adds the valid ones that are not in the history yet.
+ */
+pub fn import(history: &mut History, contents: &str) -> Result<ImportReport, String> {
+    let (version, entries) = read(contents)?;
+    let mut report = ImportReport { version, imported: 0, duplicates: 0, errors: Vec::new() };
+    for (line, entry) in entries {
+        let entry = match entry.and_then(validate) {
+            Ok(entry) => entry,
+            Err(message) => {
+                report.errors.push(ImportError { line, message });
+                continue;
+            },
+        };
+        let duplicate = history.entries().iter().any(|existing| {
+            existing.expression == entry.expression && existing.target == entry.target && existing.result == entry.result
+        });
+        if duplicate {
+            report.duplicates += 1;
+            continue;
+        }
+        history.insert(entry.expression, entry.target, entry.result, entry.timestamp)?;
+        report.imported += 1;
+    }
+    Ok(report)
+}
+
+/**
+ The version of the file and its entries.
+ */
+fn read(contents: &str) -> Result<(u64, Lines), String> {
+    let mut lines = contents.lines().enumerate().map(|(i, line)| (i + 1, line)).filter(|(_, line)| !line.trim().is_empty()).peekable();
+    let first = match lines.peek() {
+        Some((_, line)) => serde_json::from_str::<Value>(line).ok(),
+        None => return Ok((VERSION, Vec::new())),
+    };
+    let version = match first.as_ref().and_then(|value| value.get("version")) {
+        Some(version) => {
+            let version = version.as_u64().ok_or("The version has to be a whole number")?;
+            lines.next();
+            version
+        },
+        None if first.as_ref().map_or(false, |value| value.get("expression").is_some()) => VERSION,
+        //anything but an entry on the first line has to be a whole results.json
+        None => 0,
+    };
+    match version {
+        0 => legacy(contents).map(|entries| (0, entries)),
+        VERSION => Ok((VERSION, lines.map(|(line, text)| (line, serde_json::from_str::<Imported>(text).map_err(|err| err.to_string()))).collect())),
+        _ => Err(format!("The file is of version {} but only versions up to {} can be imported", version, VERSION)),
+    }
+}
+
+/**
+ Reads a results.json, the target of an entry follows the kind of its result.
+ */
+fn legacy(contents: &str) -> Result<Lines, String> {
+    let results = serde_json::from_str::<serde_json::Map<String, Value>>(contents)
+        .map_err(|err| format!("Line {}: {}", err.line(), err))?;
+    Ok(results.into_iter().map(|(expression, result)| {
+        //the line the expression is written on, a results.json written by the app has a single line
+        let line = contents.find(&serde_json::to_string(&expression).unwrap_or_default())
+            .map_or(1, |i| contents[..i].matches('\n').count() + 1);
+        let imported = serde_json::from_value::<Constant>(result).map_err(|err| format!("{}: {}", expression, err)).map(|result| {
+            let target = match result {
+                Constant::Float(_) => Target::Float,
+                Constant::Binary(_) => Target::Binary,
+                Constant::Hexadecimal(_) => Target::Hexadecimal,
+                Constant::Octal(_) => Target::Octal,
+                Constant::Boolean(_) => Target::Boolean,
+                _ => Target::Integer,
+            };
+            Imported { expression, target, result, timestamp: None }
+        });
+        (line, imported)
+    }).collect())
+}
+
+/**
+ Turns away entries that could not have come from a calculation.
+ */
+fn validate(entry: Imported) -> Result<Imported, String> {
+    if entry.expression.trim().is_empty() {
+        return Err("The expression is empty".to_string());
+    }
+    if entry.result == Constant::None {
+        return Err(format!("{} has no result", entry.expression));
+    }
+    Parser::new(&entry.expression).parse().map_err(|err| format!("{}: {}", entry.expression, err))?;
+    Ok(entry)
+}
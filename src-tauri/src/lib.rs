@@ -1,13 +1,14 @@
-use parser::constants::{Constant, Target};
+pub mod parser;
+pub mod logger;
+pub mod history;
+pub mod export;
+pub mod import;
+pub mod settings;
+#[cfg(feature = "app")]
+mod app;
 
-mod parser;
-mod logger;
-
-#[tauri::command]
-async fn calculate(expression: &str, target: Target) -> Result<Constant, String> {
-  let mut parser = parser::calculate::Parser::new(expression);
-  parser.execute(target)
-}
+#[cfg(feature = "app")]
+pub use app::run;
 
 #[cfg(test)]
 mod tests{
@@ -19,6 +20,13 @@ mod tests{
     let result = parser.execute(Target::Integer);
     let expected = Constant::Integer(3);
     assert_eq!(result, Ok(expected));
+    //a result that does not fit in 64 bits is an error rather than a wrapped value
+    for expression in ["9223372036854775807 + 1", "-9223372036854775807 - 2", "4294967296 * 4294967296", "-(-9223372036854775807 - 1)", "1 << 64", "1 >> (-1)"] {
+      parser = Parser::new(expression);
+      assert_eq!(parser.execute(Target::Integer), Err("The result overflows 64 bits".to_string()), "{}", expression);
+    }
+    parser = Parser::new("1 << 63");
+    assert_eq!(parser.execute(Target::Integer), Ok(Constant::Integer(i64::MIN)));
   }
   #[test]
   fn test_arithmetic_floats() {
@@ -33,7 +41,8 @@ mod tests{
     use crate::parser::calculate::Parser;
     let mut parser = Parser::new("(-1 + 0b1) - 3.0");
     let result = parser.execute(Target::Hexadecimal);
-    let expected = Constant::Hexadecimal((0xfffffffffffffffd as u64) as i64);
+    //3.0 keeps the result a float, so the target shows the bits of -3.0
+    let expected = Constant::Hexadecimal((0xc008000000000000 as u64) as i64);
     assert_eq!(result, Ok(expected));
   }
   #[test]
@@ -75,12 +84,603 @@ mod tests{
     let expected = Constant::Integer(9);
     assert_eq!(result, Ok(expected));
   }
-}
+  #[test]
+  fn test_comparison() {
+    use crate::parser::calculate::Parser;
+    let mut parser = Parser::new("(0x1f & 0x3) == 0x3");
+    let mut result = parser.execute(Target::Boolean);
+    assert_eq!(result, Ok(Constant::Boolean(true)));
+    parser = Parser::new("1 + 2 < 3 || 2 >= 1.5");
+    result = parser.execute(Target::Integer);
+    assert_eq!(result, Ok(Constant::Integer(1)));
+    parser = Parser::new("1 << 2 != 4 && 1");
+    result = parser.execute(Target::Boolean);
+    assert_eq!(result, Ok(Constant::Boolean(false)));
+    parser = Parser::new("true && !false");
+    result = parser.execute(Target::Boolean);
+    assert_eq!(result, Ok(Constant::Boolean(true)));
+    parser = Parser::new("(3 > 2) == true ? 10 : 20");
+    result = parser.execute(Target::Integer);
+    assert_eq!(result, Ok(Constant::Integer(10)));
+    parser = Parser::new("false + 1");
+    result = parser.execute(Target::Integer);
+    assert_eq!(result, Ok(Constant::Integer(1)));
+  }
+  #[test]
+  fn test_conditional() {
+    use crate::parser::calculate::Parser;
+    let mut parser = Parser::new("2 > 1 ? 10 + 1 : 20");
+    let mut result = parser.execute(Target::Integer);
+    assert_eq!(result, Ok(Constant::Integer(11)));
+    parser = Parser::new("0 ? 1 : 0 ? 2 : 3");
+    result = parser.execute(Target::Integer);
+    assert_eq!(result, Ok(Constant::Integer(3)));
+    //the branch that is not taken is never evaluated
+    parser = Parser::new("0 && 1 / 0 || 1 ? 5 : 1 / 0");
+    result = parser.execute(Target::Integer);
+    assert_eq!(result, Ok(Constant::Integer(5)));
+  }
+  #[test]
+  fn test_division_kinds() {
+    use crate::parser::calculate::Parser;
+    let cases = [
+      ("-7 / 2", Constant::Integer(-3)),
+      ("-7 // 2", Constant::Integer(-4)),
+      ("7 // -2", Constant::Integer(-4)),
+      ("-7 div 2", Constant::Integer(-4)),
+      ("7 div -2", Constant::Integer(-3)),
+      ("-7 % 2", Constant::Integer(-1)),
+      ("-7 rem 2", Constant::Integer(-1)),
+      ("-7 mod 2", Constant::Integer(1)),
+      ("7 mod -2", Constant::Integer(1)),
+    ];
+    for (expression, expected) in cases {
+      let mut parser = Parser::new(expression);
+      assert_eq!(parser.execute(Target::Integer), Ok(expected), "{}", expression);
+    }
+    let mut parser = Parser::new("7 /. 2");
+    assert_eq!(parser.execute(Target::Float), Ok(Constant::Float(3.5)));
+    //a float written without a fraction is still a float
+    parser = Parser::new("7 / 2.0");
+    assert_eq!(parser.evaluate_in(&Default::default()), Ok(Constant::Float(3.5)));
+    parser = Parser::new("2.0 * 3");
+    assert_eq!(parser.evaluate_in(&Default::default()), Ok(Constant::Float(6.0)));
+    parser = Parser::new("100000000000000000000.0 / 4");
+    assert_eq!(parser.evaluate_in(&Default::default()), Ok(Constant::Float(25000000000000000000.0)));
+    parser = Parser::new("-7.5 // 2");
+    assert_eq!(parser.execute(Target::Float), Ok(Constant::Float(-4.0)));
+    //the smallest integer divided by -1 does not fit in 64 bits whichever way it is divided
+    for operator in ["/", "//", "div", "%", "rem", "mod"] {
+      let expression = format!("(-9223372036854775807 - 1) {} (-1)", operator);
+      let mut parser = Parser::new(&expression);
+      assert_eq!(parser.execute(Target::Integer), Err("The quotient overflows 64 bits".to_string()), "{}", expression);
+    }
+    parser = Parser::new("(-9223372036854775807 - 1) // 2");
+    assert_eq!(parser.execute(Target::Integer), Ok(Constant::Integer(-4611686018427387904)));
+    //a negative exponent gives a float and a power that does not fit is an error
+    parser = Parser::new("2 ** -1");
+    assert_eq!(parser.execute(Target::Float), Ok(Constant::Float(0.5)));
+    parser = Parser::new("(-2) ** 63");
+    assert_eq!(parser.execute(Target::Integer), Ok(Constant::Integer(i64::MIN)));
+    parser = Parser::new("2 ** 63");
+    assert_eq!(parser.execute(Target::Integer), Err("The result overflows 64 bits".to_string()));
+    parser = Parser::new("1 ** 4294967296 + (-1) ** 4294967297");
+    assert_eq!(parser.execute(Target::Integer), Ok(Constant::Integer(0)));
+  }
+  #[test]
+  fn test_root() {
+    use crate::parser::calculate::Parser;
+    let mut parser = Parser::new("3√27 + √16");
+    let result = parser.execute(Target::Integer);
+    assert_eq!(result, Ok(Constant::Integer(7)));
+  }
+  #[test]
+  fn test_number_theory() {
+    use crate::parser::calculate::Parser;
+    let cases = [
+      ("gcd(48, 18) + lcm(4, 6)", Target::Integer, Constant::Integer(18)),
+      ("factorial(20)", Target::Integer, Constant::Integer(2432902008176640000)),
+      ("nCr(52, 5) - nPr(5, 2)", Target::Integer, Constant::Integer(2598940)),
+      ("modpow(3, 200, 1000000007)", Target::Integer, Constant::Integer(136318165)),
+      ("modinv(3, 11)", Target::Integer, Constant::Integer(4)),
+      ("isprime(2305843009213693951)", Target::Boolean, Constant::Boolean(true)),
+      ("nextprime(1 << 32)", Target::Integer, Constant::Integer(4294967311)),
+      ("factor(360)", Target::Integer, Constant::Factors(vec![(2, 3), (3, 2), (5, 1)])),
+    ];
+    for (expression, target, expected) in cases {
+      let mut parser = Parser::new(expression);
+      assert_eq!(parser.execute(target), Ok(expected), "{}", expression);
+    }
+    let mut parser = Parser::new("factor(600851475143) * 1");
+    assert_eq!(parser.execute(Target::Integer), Ok(Constant::Integer(600851475143)));
+    parser = Parser::new("modinv(4, 8)");
+    assert_eq!(parser.execute(Target::Integer), Err("4 has no inverse modulo 8".to_string()));
+    parser = Parser::new("factorial(21)");
+    assert_eq!(parser.execute(Target::Integer), Err("factorial(21) does not fit in 64 bits".to_string()));
+  }
+  #[test]
+  fn test_modular() {
+    use crate::parser::{calculate::Parser, context::Context};
+    let mut parser = Parser::new("mod 2**32 { 0xffffffff * 0xffffffff + 5 }");
+    assert_eq!(parser.execute(Target::Integer), Ok(Constant::Integer(6)));
+    parser = Parser::new("mod 7 { 3 / 5 - 6 }");
+    assert_eq!(parser.execute(Target::Integer), Ok(Constant::Integer(3)));
+    parser = Parser::new("mod 13 { 2 ** -1 } + 10 mod 4");
+    assert_eq!(parser.execute(Target::Integer), Ok(Constant::Integer(9)));
+    parser = Parser::new("mod 12 { -5 }");
+    assert_eq!(parser.execute(Target::Integer), Ok(Constant::Integer(7)));
+    parser = Parser::new("mod 8 { 1 / 2 }");
+    assert_eq!(parser.execute(Target::Integer), Err("2 has no inverse modulo 8".to_string()));
+    parser = Parser::new("mod 10 { 1.5 * 2 }");
+    assert_eq!(parser.execute(Target::Integer), Err("Modular arithmetic only works on integers".to_string()));
+    //division with a remainder has no meaning modulo N
+    for operator in ["%", "//", "div", "mod"] {
+      let expression = format!("mod 7 {{ 10 {} 4 }}", operator);
+      let mut parser = Parser::new(&expression);
+      assert_eq!(parser.execute(Target::Integer), Err(format!("{} does not work modulo 7, / multiplies by the inverse", operator)), "{}", operator);
+    }
+    parser = Parser::new("mod 7 { 2 ** (10 mod 4) }");
+    assert_eq!(parser.execute(Target::Integer), Ok(Constant::Integer(4)));
+    parser = Parser::new("7 * 8 + 1");
+    assert_eq!(parser.execute_in(Target::Integer, &Context { modulus: Some(10), ..Context::default() }), Ok(Constant::Integer(7)));
+  }
+  #[test]
+  fn test_gf2() {
+    use crate::parser::calculate::Parser;
+    let cases = [
+      ("clmul(0b11, 0b11)", Target::Binary, Constant::Binary(0b101)),
+      ("clmul(0x87, 0x2)", Target::Hexadecimal, Constant::Hexadecimal(0x10e)),
+      ("polydiv(0b101, 0b11)", Target::Binary, Constant::Binary(0b11)),
+      ("polymod(0x100, 0x11b)", Target::Hexadecimal, Constant::Hexadecimal(0x1b)),
+      ("polymod(clmul(0x53, 0xca), 0x11b)", Target::Integer, Constant::Integer(1)),
+      ("reflect(0x04c11db7, 32)", Target::Hexadecimal, Constant::Hexadecimal(0xedb88320)),
+      ("reflect(1)", Target::Hexadecimal, Constant::Hexadecimal(i64::MIN)),
+    ];
+    for (expression, target, expected) in cases {
+      let mut parser = Parser::new(expression);
+      assert_eq!(parser.execute(target), Ok(expected), "{}", expression);
+    }
+    let mut parser = Parser::new("clmul(0xffffffffffff, 0xffffffffffff)");
+    assert_eq!(parser.execute(Target::Integer), Err("The result of clmul does not fit in 64 bits".to_string()));
+    parser = Parser::new("polymod(5, 0)");
+    assert_eq!(parser.execute(Target::Integer), Err("Division by zero".to_string()));
+  }
+  #[test]
+  fn test_lists() {
+    use crate::parser::calculate::Parser;
+    let cases = [
+      ("[1, 2 * 3, []]", Target::Integer, Constant::List(vec![Constant::Integer(1), Constant::Integer(6), Constant::List(vec![])])),
+      ("3..1", Target::Integer, Constant::List(vec![Constant::Integer(3), Constant::Integer(2), Constant::Integer(1)])),
+      ("[0x1, 0b10]", Target::Hexadecimal, Constant::List(vec![Constant::Hexadecimal(1), Constant::Hexadecimal(2)])),
+      ("sum(1..100)", Target::Integer, Constant::Integer(5050)),
+      ("product([2, 3, 4])", Target::Integer, Constant::Integer(24)),
+      ("mean(1, 2, 3, 4)", Target::Float, Constant::Float(2.5)),
+      ("median([5, 1, 3])", Target::Integer, Constant::Integer(3)),
+      ("median(1..4)", Target::Float, Constant::Float(2.5)),
+      ("mode([1, 3, 3, 2, 2])", Target::Integer, Constant::Integer(2)),
+      ("min(4, -2, 7) + max([4, -2, 7])", Target::Integer, Constant::Integer(5)),
+      ("variance([2, 4, 4, 4, 5, 5, 7, 9])", Target::Float, Constant::Float(32.0 / 7.0)),
+      ("percentile(1..5, 25)", Target::Float, Constant::Float(2.0)),
+    ];
+    for (expression, target, expected) in cases {
+      let mut parser = Parser::new(expression);
+      assert_eq!(parser.execute(target), Ok(expected), "{}", expression);
+    }
+    let mut parser = Parser::new("mean([])");
+    assert_eq!(parser.execute(Target::Float), Err("mean needs at least 1 number".to_string()));
+    parser = Parser::new("[1, 2] + 1");
+    assert_eq!(parser.execute(Target::Integer), Err("Invalid addition operation".to_string()));
+    //every operator names itself when it can not work on its operands
+    for (expression, operation) in [("(x -> x) * 2", "multiplication"), ("(x -> x) / 2", "division"), ("(x -> x) ** 2", "exponentiation"), ("2 √ (x -> x)", "root"), ("!(x -> x)", "not"), ("-(x -> x)", "negation")] {
+      parser = Parser::new(expression);
+      assert_eq!(parser.execute(Target::Integer), Err(format!("Invalid {} operation", operation)), "{}", expression);
+    }
+    let list: Constant = serde_json::from_str("{\"List\":\"[0x1, [2, 3.5], true]\"}").unwrap();
+    assert_eq!(list, Constant::List(vec![Constant::Hexadecimal(1), Constant::List(vec![Constant::Integer(2), Constant::Float(3.5)]), Constant::Boolean(true)]));
+  }
+  #[test]
+  fn test_higher_order() {
+    use crate::parser::calculate::Parser;
+    let list = |items: &[i64]| Constant::List(items.iter().map(|i| Constant::Integer(*i)).collect());
+    let cases = [
+      ("map([1, 2, 3], x -> x * 2)", list(&[2, 4, 6])),
+      ("filter(1..10, x -> x & 1)", list(&[1, 3, 5, 7, 9])),
+      ("reduce([3, 5, 6], 0, (a, x) -> a ^ x)", Constant::Integer(0)),
+      ("reduce(1..5, (a, x) -> a * x)", Constant::Integer(120)),
+      ("zip([1, 2, 3], [4, 5])", Constant::List(vec![list(&[1, 4]), list(&[2, 5])])),
+      ("map(1..3, x -> sum(map(1..x, y -> x * y)))", list(&[1, 6, 18])),
+    ];
+    for (expression, expected) in cases {
+      let mut parser = Parser::new(expression);
+      assert_eq!(parser.execute(Target::Integer), Ok(expected), "{}", expression);
+    }
+    let mut parser = Parser::new("map([1, 2], (a, x) -> a)");
+    assert_eq!(parser.execute(Target::Integer), Err("The function takes 2 arguments but got 1".to_string()));
+    parser = Parser::new("x + 1");
+    assert_eq!(parser.execute(Target::Integer), Err("Unknown variable: x".to_string()));
+  }
+  #[test]
+  fn test_matrices() {
+    use crate::parser::calculate::Parser;
+    let cases = [
+      ("[[1, 2], [3, 4]] + [[1, 1], [1, 1]]", Constant::Matrix(vec![vec![2.0, 3.0], vec![4.0, 5.0]])),
+      ("[[1, 2], [3, 4]] * [[5, 6], [7, 8]] - [[1, 0], [0, 1]]", Constant::Matrix(vec![vec![18.0, 22.0], vec![43.0, 49.0]])),
+      ("2 * transpose([[1, 2, 3]])", Constant::Matrix(vec![vec![2.0], vec![4.0], vec![6.0]])),
+      ("[[1, 2], [3, 4]] * [1, 1]", Constant::List(vec![Constant::Integer(3), Constant::Integer(7)])),
+      ("det([[1, 2, 3], [4, 5, 6], [7, 8, 10]])", Constant::Integer(-3)),
+      ("inv([[2, 0], [0, 4]])", Constant::Matrix(vec![vec![0.5, 0.0], vec![0.0, 0.25]])),
+      ("rank([[1, 2], [2, 4]])", Constant::Integer(1)),
+      ("linsolve([[2, 1], [1, 3]], [5, 10])", Constant::List(vec![Constant::Integer(1), Constant::Integer(3)])),
+    ];
+    for (expression, expected) in cases {
+      let mut parser = Parser::new(expression);
+      assert_eq!(parser.execute(Target::Integer), Ok(expected), "{}", expression);
+    }
+    //a rotation times its transpose is the identity
+    let mut parser = Parser::new("[[0, -1, 0], [1, 0, 0], [0, 0, 1]] * transpose([[0, -1, 0], [1, 0, 0], [0, 0, 1]])");
+    assert_eq!(parser.execute(Target::Integer), Ok(Constant::Matrix(vec![vec![1.0, 0.0, 0.0], vec![0.0, 1.0, 0.0], vec![0.0, 0.0, 1.0]])));
+    parser = Parser::new("inv([[1, 2], [2, 4]])");
+    assert_eq!(parser.execute(Target::Integer), Err("The matrix is singular".to_string()));
+    parser = Parser::new("[[1, 2]] * [[1, 2]]");
+    assert_eq!(parser.execute(Target::Integer), Err("Can not multiply a 1x2 matrix by a 1x2 matrix".to_string()));
+  }
+  #[test]
+  fn test_script() {
+    use crate::parser::session::Session;
+    let mut session = Session::default();
+    let results = session.run_script("# a derivation\nx = 3; y = x * 2\nf(a) = a + y\nf(1)\nfact(n) = n <= 1 ? 1 : n * fact(n - 1)\nfact(5)\nans * 2\nmap([1,\n 2], f)\nz + 1", Target::Integer);
+    let lines: Vec<usize> = results.iter().map(|statement| statement.line).collect();
+    assert_eq!(lines, vec![2, 2, 3, 4, 5, 6, 7, 8, 10]);
+    assert_eq!(results[1].result, Ok(Constant::Integer(6)));
+    assert_eq!(results[3].result, Ok(Constant::Integer(7)));
+    assert_eq!(results[5].result, Ok(Constant::Integer(120)));
+    assert_eq!(results[6].result, Ok(Constant::Integer(240)));
+    assert_eq!(results[7].result, Ok(Constant::List(vec![Constant::Integer(7), Constant::Integer(8)])));
+    assert_eq!(results[8].result, Err("Unknown variable: z".to_string()));
+    assert!(session.run("x = ", Target::Integer).is_err());
+  }
+  #[test]
+  fn test_implicit_multiplication() {
+    use crate::parser::calculate::{Parser, Implicit};
+    let mut parser = Parser::new("2(3 + 4)");
+    assert_eq!(parser.execute(Target::Integer), Ok(Constant::Integer(14)));
+    let mut parser = Parser::new("(2 + 3)(2 - 3)");
+    assert_eq!(parser.execute(Target::Integer), Ok(Constant::Integer(-5)));
+    let mut parser = Parser::new("2pi");
+    assert_eq!(parser.execute(Target::Float), Ok(Constant::Float(std::f64::consts::TAU)));
+    let mut parser = Parser::new("map([1, 2], x -> 3x**2 + 1)");
+    assert_eq!(parser.execute(Target::Integer), Ok(Constant::List(vec![Constant::Integer(4), Constant::Integer(13)])));
+    let mut parser = Parser::new("12 / 2(3)");
+    assert_eq!(parser.execute(Target::Integer), Ok(Constant::Integer(2)));
+    let mut parser = Parser::with_implicit("12 / 2(3)", Implicit::Strict);
+    assert_eq!(parser.execute(Target::Integer), Ok(Constant::Integer(18)));
+    let mut parser = Parser::new("12 - 2 - 3");
+    assert_eq!(parser.execute(Target::Integer), Ok(Constant::Integer(7)));
+    let mut parser = Parser::new("2 ** 3 ** 2");
+    assert_eq!(parser.execute(Target::Integer), Ok(Constant::Integer(512)));
+    let mut parser = Parser::new("gcd(4, 6)(2)");
+    assert_eq!(parser.execute(Target::Integer), Ok(Constant::Integer(4)));
+  }
+  #[test]
+  fn test_percentages() {
+    use crate::parser::calculate::Parser;
+    let mut parser = Parser::new("200 * 15%");
+    assert_eq!(parser.execute(Target::Float), Ok(Constant::Float(30.0)));
+    let mut parser = Parser::new("80 + 10%");
+    assert_eq!(parser.execute(Target::Float), Ok(Constant::Float(88.0)));
+    let mut parser = Parser::new("80 - 25% - 5");
+    assert_eq!(parser.execute(Target::Float), Ok(Constant::Float(55.0)));
+    let mut parser = Parser::new("15% of 200 + 1");
+    assert_eq!(parser.execute(Target::Float), Ok(Constant::Float(31.0)));
+    let mut parser = Parser::new("(1 + 1)%");
+    assert_eq!(parser.execute(Target::Float), Ok(Constant::Float(0.02)));
+    let mut parser = Parser::new("17 % 5");
+    assert_eq!(parser.execute(Target::Integer), Ok(Constant::Integer(2)));
+    let mut parser = Parser::new("17 % (2 + 3)");
+    assert_eq!(parser.execute(Target::Integer), Ok(Constant::Integer(2)));
+    //percent and of are still free to be names, of is only a word right after a percentage
+    let mut session = crate::parser::session::Session::default();
+    let results = session.run_script("percent = 50; of = 4\npercent * of\n25% of of\npercent% of 10", None);
+    let results: Vec<_> = results.into_iter().map(|statement| statement.result).collect();
+    assert_eq!(results, [Ok(Constant::Integer(50)), Ok(Constant::Integer(4)), Ok(Constant::Integer(200)), Ok(Constant::Float(1.0)), Ok(Constant::Float(5.0))]);
+  }
+  #[test]
+  fn test_format_options() {
+    use crate::parser::format::{FormatOptions, Notation};
+    let describe = |constant: Constant, options: FormatOptions| constant.describe(&options).1;
+    assert_eq!(describe(Constant::Hexadecimal(255), FormatOptions::default()), "0xff");
+    assert_eq!(describe(Constant::Float(2.5), FormatOptions::default()), "2.5");
+    assert_eq!(describe(Constant::Float(1.23456), FormatOptions { decimals: Some(2), ..FormatOptions::default() }), "1.23");
+    assert_eq!(describe(Constant::Float(123456.0), FormatOptions { significant: Some(2), grouping: true, ..FormatOptions::default() }), "120,000");
+    assert_eq!(describe(Constant::Float(-1234.5), FormatOptions { notation: Notation::Scientific, ..FormatOptions::default() }), "-1.2345e3");
+    assert_eq!(describe(Constant::Float(0.00047), FormatOptions { notation: Notation::Engineering, ..FormatOptions::default() }), "470e-6");
+    assert_eq!(describe(Constant::Float(9.9996), FormatOptions { notation: Notation::Scientific, decimals: Some(2), ..FormatOptions::default() }), "1.00e1");
+    assert_eq!(describe(Constant::Integer(-1234567), FormatOptions { grouping: true, ..FormatOptions::default() }), "-1,234,567");
+    assert_eq!(describe(Constant::Binary(5), FormatOptions { width: Some(8), grouping: true, ..FormatOptions::default() }), "0b0000_0101");
+    assert_eq!(describe(Constant::Hexadecimal(-1), FormatOptions { width: Some(16), uppercase: true, prefix: false, ..FormatOptions::default() }), "FFFF");
+    assert_eq!(describe(Constant::Octal(8), FormatOptions { width: Some(8), ..FormatOptions::default() }), "0o010");
+    let options = FormatOptions { decimals: Some(1), ..FormatOptions::default() };
+    assert_eq!(describe(Constant::List(vec![Constant::Float(0.26), Constant::Integer(2)]), options), "[0.3, 2]");
+  }
+  #[test]
+  fn test_representations() {
+    use crate::parser::{calculate::Parser, context::Context, format::{FormatOptions, Representations}};
+    let constant = Parser::new("200 + 55").evaluate_in(&Context::default()).unwrap();
+    let all = Representations::new(constant.clone(), &FormatOptions::default()).unwrap();
+    assert_eq!((all.signed.as_str(), all.unsigned.as_str()), ("255", "255"));
+    assert_eq!((all.hexadecimal.as_str(), all.octal.as_str(), all.binary.as_str()), ("0xff", "0o377", "0b11111111"));
+    assert_eq!((all.float.as_str(), all.ieee.as_str()), ("255", "0x406fe00000000000"));
+    let byte = Representations::new(constant, &FormatOptions { width: Some(8), ..FormatOptions::default() }).unwrap();
+    assert_eq!((byte.signed.as_str(), byte.unsigned.as_str(), byte.binary.as_str()), ("-1", "255", "0b11111111"));
+    let negative = Representations::new(Constant::Integer(-2), &FormatOptions::default()).unwrap();
+    assert_eq!((negative.unsigned.as_str(), negative.hexadecimal.as_str()), ("18446744073709551614", "0xfffffffffffffffe"));
+    assert!(Representations::new(Constant::List(vec![]), &FormatOptions::default()).is_err());
+  }
+  #[test]
+  fn test_fractions() {
+    use crate::parser::{calculate::Parser, format::FormatOptions};
+    let options = FormatOptions { fraction: true, ..FormatOptions::default() };
+    assert_eq!(Constant::Float(0.333333).describe(&options).1, "1/3");
+    assert_eq!(Constant::Float(std::f64::consts::PI).describe(&options).1, "355/113");
+    assert_eq!(Constant::Float(-2.5).describe(&options).1, "-5/2");
+    assert_eq!(Constant::Float(4.0).describe(&options).1, "4");
+    let loose = FormatOptions { tolerance: Some(0.01), ..options.clone() };
+    assert_eq!(Constant::Float(std::f64::consts::PI).describe(&loose).1, "22/7");
+    let bounded = FormatOptions { denominator: Some(100), ..options };
+    assert_eq!(Constant::Float(std::f64::consts::PI).describe(&bounded).1, "311/99");
+    let cf = |terms: &[i64]| Constant::List(terms.iter().map(|i| Constant::Integer(*i)).collect());
+    let mut parser = Parser::new("cf(3.14159)");
+    assert_eq!(parser.execute(Target::Integer), Ok(cf(&[3, 7, 15, 1, 25, 1, 7, 4])));
+    let mut parser = Parser::new("cf(pi, 4)");
+    assert_eq!(parser.execute(Target::Integer), Ok(cf(&[3, 7, 15, 1])));
+    let mut parser = Parser::new("cf(0.1)");
+    assert_eq!(parser.execute(Target::Integer), Ok(cf(&[0, 10])));
+  }
+  #[test]
+  fn test_symbolic() {
+    use crate::parser::calculate::Parser;
+    let symbolic = |expression: &str| match Parser::new(expression).execute(Target::Integer) {
+      Ok(Constant::Expression(expression)) => expression.to_string(),
+      result => format!("{:?}", result),
+    };
+    assert_eq!(symbolic("diff(x ** 3 + 2x, x)"), "3 * x ** 2 + 2");
+    assert_eq!(symbolic("diff(a * x ** 2 - x / 2, x)"), "2 * a * x - 0.5");
+    assert_eq!(symbolic("diff(sin(x ** 2), x)"), "2 * x * cos(x ** 2)");
+    assert_eq!(symbolic("diff(1 / x, x)"), "-1 / x ** 2");
+    assert_eq!(symbolic("diff(2 ** x, x)"), "2 ** x * ln(2)");
+    assert_eq!(symbolic("diff(diff(x ** 3, x), x)"), "6 * x");
+    assert_eq!(symbolic("simplify(2x + 3x - x * x + 1 + 1)"), "-(x ** 2) + 5 * x + 2");
+    assert_eq!(symbolic("simplify(2(y + 1) - 2y)"), "Ok(Integer(2))");
+    assert_eq!(symbolic("simplify((a - b) - (a - b) * 2)"), "-a + b");
+    assert_eq!(symbolic("simplify(x * y / x)"), "y");
+    let derivative = Parser::new("diff(x ** 3, x)").execute(Target::Integer).unwrap();
+    let json = serde_json::to_string(&derivative).unwrap();
+    assert_eq!(serde_json::from_str::<Constant>(&json).unwrap(), derivative);
+    assert_eq!(symbolic("diff(x ** 2, 3)"), "Err(\"diff needs a variable name as its second argument\")");
+    assert_eq!(symbolic("diff(x < 2, x)"), "Err(\"< can not be used symbolically\")");
+    //an expression that is written out reads back the same, however two operators are grouped
+    let operators = ["**", "*", "/", "mod", "+", "-", "<<", "<", "==", "&", "^", "&&", "||"];
+    for first in operators {
+      for second in operators {
+        for written in [format!("(a {} b) {} c", first, second), format!("a {} (b {} c)", first, second)] {
+          let expression = Parser::new(&written).parse().unwrap();
+          assert_eq!(Parser::new(&expression.to_string()).parse(), Ok(expression), "{}", written);
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn test_solve() {
+    use crate::parser::calculate::Parser;
+    let solve = |expression: &str| Parser::new(expression).execute(Target::Float);
+    assert_eq!(solve("root(x ** 3 - x, x, -2, 2)"), Ok(Constant::List(vec![Constant::Float(-1.0), Constant::Float(0.0), Constant::Float(1.0)])));
+    //roots found by bisection are only as close as the last step
+    let close = |expression: &str, expected: &[f64]| match solve(expression) {
+      Ok(Constant::List(roots)) => roots.len() == expected.len() && roots.iter().zip(expected).all(|(root, x)| {
+        let root: f64 = root.clone().into();
+        (root - x).abs() < 1e-9
+      }),
+      _ => false,
+    };
+    assert!(close("solve(x ** 2 == 2, x)", &[-(2f64.sqrt()), 2f64.sqrt()]));
+    assert_eq!(solve("solve(x ** 2, x)"), Ok(Constant::List(vec![Constant::Float(0.0)])));
+    //a goal seek, the divisor that gives 115200 baud from a 48MHz clock
+    assert!(close("solve(48000000 / (16 * d) == 115200, d, 1, 100)", &[48000000.0 / 16.0 / 115200.0]));
+    assert_eq!(solve("root(1 / x, x, -1, 1)"), Ok(Constant::List(vec![])));
+    assert_eq!(solve("solve(x ** 2 == -1, x)"), Ok(Constant::List(vec![])));
+    assert_eq!(solve("root(x, 2, 0, 1)"), Err("root needs a variable name as its second argument".to_string()));
+    assert_eq!(solve("root(x, x, 0)"), Err("root takes 4 arguments but got 3".to_string()));
+  }
+
+  #[test]
+  fn test_integrate() {
+    use crate::parser::calculate::Parser;
+    let calculate = |expression: &str| Parser::new(expression).execute(Target::Float);
+    let close = |expression: &str, expected: f64| match calculate(expression) {
+      Ok(Constant::Float(value)) => (value - expected).abs() < 1e-9 * expected.abs().max(1.0),
+      _ => false,
+    };
+    assert!(close("integrate(x ** 2, x, 0, 3)", 9.0));
+    assert!(close("integrate(x, x, 3, 0)", -4.5));
+    assert!(close("integrate(e ** -(x ** 2), x, -10, 10)", std::f64::consts::PI.sqrt()));
+    assert!(close("integrate(x ** -0.5, x, 0, 1)", 2.0));
+    assert_eq!(calculate("integrate(1 / x, x, -1, 1)"), Err("The integral does not converge".to_string()));
+    assert_eq!(calculate("sum(k ** 2, k, 1, 10)"), Ok(Constant::Float(385.0)));
+    assert_eq!(Parser::new("prod(k, k, 1, 20)").execute(Target::Integer), Ok(Constant::Integer(2432902008176640000)));
+    assert!(close("sum(1 /. k ** 2, k, 1, 10000)", 1.6448340718480652));
+    assert_eq!(calculate("sum(k, k, 5, 1)"), Ok(Constant::Float(0.0)));
+    //four numbers are still added up as a list
+    assert_eq!(calculate("sum(1, 2, 3, 4)"), Ok(Constant::Float(10.0)));
+    assert_eq!(calculate("prod(k, k, 1, 30)"), Err("The result of prod does not fit in 64 bits".to_string()));
+    assert_eq!(calculate("sum(k, k, 1.5, 3)"), Err("sum needs whole numbers as the ends of its range".to_string()));
+    //a term without the variable is repeated for every k
+    assert_eq!(calculate("sum(2, k, 1, 10)"), Ok(Constant::Float(20.0)));
+    assert_eq!(Parser::new("prod(3, k, 1, 4)").execute(Target::Integer), Ok(Constant::Integer(81)));
+    assert_eq!(calculate("sum(k, k, -9223372036854775807 - 1, 9223372036854775807)"), Err("sum can not take more than 1000000 terms".to_string()));
+    match calculate("integrate_err(x ** 2, x, 0, 3)") {
+      Ok(Constant::List(items)) => match items.as_slice() {
+        [Constant::Float(value), Constant::Float(error)] => assert!((value - 9.0).abs() < 1e-9 && *error >= 0.0 && *error <= 1e-9, "{} {}", value, error),
+        items => panic!("{:?}", items),
+      },
+      result => panic!("{:?}", result),
+    }
+    assert_eq!(calculate("integrate_err(1 / x, x, -1, 1)"), Err("The integral does not converge".to_string()));
+  }
+
+  #[test]
+  fn test_sample() {
+    use crate::parser::{calculate::Parser, context::Context, numeric::sample};
+    let sampled = |expression: &str, from: f64, to: f64, n: usize| sample(&Parser::new(expression).parse().unwrap(), "x", from, to, n, &Context::default());
+    let parabola = sampled("x ** 2", -1.0, 1.0, 5).unwrap();
+    assert_eq!(parabola.points.iter().map(|point| point.x).collect::<Vec<f64>>(), vec![-1.0, -0.5, 0.0, 0.5, 1.0]);
+    assert_eq!(parabola.points[1].y, Ok(0.25));
+    assert!(parabola.breaks.is_empty());
+    //the pole of 1 / x lies between the 10th and 11th point, the steps of floor at 1 and 2
+    assert_eq!(sampled("1 / x", -1.0, 1.0, 20).unwrap().breaks, vec![9]);
+    assert_eq!(sampled("x // 1", 0.0, 3.0, 31).unwrap().breaks, vec![9, 19, 29]);
+    assert!(sampled("x ** 3", -10.0, 10.0, 1001).unwrap().breaks.is_empty());
+    //a point that fails does not stop the others
+    let root = sampled("x ** 0.5", -1.0, 1.0, 5).unwrap();
+    assert_eq!(root.points[0].y, Err("The value is not finite".to_string()));
+    assert_eq!(root.points[4].y, Ok(1.0));
+    assert_eq!(sampled("x + y", 0.0, 1.0, 3).unwrap().points[0].y, Err("Unknown variable: y".to_string()));
+    assert_eq!(sampled("x", 0.0, 1.0, 1), Err("A sample needs between 2 and 100000 points".to_string()));
+  }
+
+  #[test]
+  fn test_history() {
+    use crate::history::History;
+    let path = std::env::temp_dir().join(format!("calcit-history-{}", std::process::id())).join("history.jsonl");
+    let _ = std::fs::remove_file(&path);
+    let mut history = History::open(path.clone()).unwrap();
+    history.append("1 + 1".to_string(), Target::Integer, Constant::Integer(2)).unwrap();
+    history.append("0xff".to_string(), Target::Hexadecimal, Constant::Hexadecimal(255)).unwrap();
+    let third = history.append("1 / 2.0".to_string(), Target::Float, Constant::Float(0.5)).unwrap();
+    assert_eq!(history.delete(&[1, 7]), Ok(1));
+    //a restart replays the file, the deletion included
+    let mut history = History::open(path.clone()).unwrap();
+    let page = history.list(0, 1);
+    assert_eq!((page.entries, page.total), (vec![third], 2));
+    assert_eq!(history.list(1, 10).entries[0].expression, "1 + 1");
+    assert_eq!(history.append("2".to_string(), Target::Integer, Constant::Integer(2)).unwrap().id, 3);
+    history.clear().unwrap();
+    assert_eq!(History::open(path.clone()).unwrap().list(0, 10).total, 0);
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+  }
 
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
-  tauri::Builder::default()
-    .invoke_handler(tauri::generate_handler![calculate])
-    .run(tauri::generate_context!())
-    .expect("error while running tauri application");
+  #[test]
+  fn test_export() {
+    use crate::{export::{export, ExportFormat}, history::Entry};
+    let entries = vec![
+      Entry { id: 0, expression: "0xf0 | 0x0f".to_string(), target: Target::Hexadecimal, result: Constant::Hexadecimal(255), timestamp: 1685620800000 },
+      Entry { id: 1, expression: "max(1, 2) < 3".to_string(), target: Target::Boolean, result: Constant::Boolean(true), timestamp: 0 },
+    ];
+    assert_eq!(export(&entries, ExportFormat::Csv).unwrap(), "id,time,expression,target,result\n\
+      0,2023-06-01 12:00:00,0xf0 | 0x0f,Hexadecimal,0xff\n\
+      1,1970-01-01 00:00:00,\"max(1, 2) < 3\",Boolean,true\n");
+    let markdown = export(&entries, ExportFormat::Markdown).unwrap();
+    assert_eq!(markdown.lines().nth(2), Some("| 2023-06-01 12:00:00 | `0xf0 \\| 0x0f` | Hexadecimal | `0xff` |"));
+    let lines = export(&entries, ExportFormat::JsonLines).unwrap();
+    assert_eq!(lines.lines().next(), Some("{\"version\":1}"));
+    let read: Vec<Entry> = lines.lines().skip(1).map(|line| serde_json::from_str(line).unwrap()).collect();
+    assert_eq!(read, entries);
+    let tape = export(&entries, ExportFormat::Html).unwrap();
+    assert!(tape.contains("<div class=\"expression\">max(1, 2) &lt; 3</div>"));
+    assert!(tape.contains("<div class=\"result\">= 0xff</div>"));
+  }
+
+  #[test]
+  fn test_import() {
+    use crate::{export::{export, ExportFormat}, history::History, import::{import, ImportError}};
+    //a hand edited constant is an error rather than a crash or None
+    assert_eq!(serde_json::from_str::<Constant>("{\"Hexadecimal\":\"0xfffffffffffffffd\"}").unwrap(), Constant::Hexadecimal(-3));
+    assert!(serde_json::from_str::<Constant>("{\"Integer\":\"twelve\"}").is_err());
+    assert!(serde_json::from_str::<Constant>("{\"Complex\":\"1\"}").is_err());
+    assert!(serde_json::from_str::<Constant>("{\"List\":\"[1, x]\"}").is_err());
+    let path = std::env::temp_dir().join(format!("calcit-import-{}", std::process::id())).join("history.jsonl");
+    let _ = std::fs::remove_file(&path);
+    let mut history = History::open(path.clone()).unwrap();
+    history.append("1 + 1".to_string(), Target::Integer, Constant::Integer(2)).unwrap();
+    //a results.json of an older release, without a version
+    let legacy = "{\n\"1 + 1\": {\"Integer\": \"2\"},\n\"0xff\": {\"Hexadecimal\": \"0xff\"},\n\"2 * 3\": {\"Integer\": \"six\"}\n}";
+    let report = import(&mut history, legacy).unwrap();
+    assert_eq!((report.version, report.imported, report.duplicates), (0, 1, 1));
+    assert_eq!(report.errors, vec![ImportError { line: 4, message: "2 * 3: six is not a valid Integer".to_string() }]);
+    assert_eq!(history.entries()[1].target, Target::Hexadecimal);
+    //an export imports into another history, lines that do not fit are reported
+    let exported = export(history.entries(), ExportFormat::JsonLines).unwrap();
+    let mut other = History::open(path.with_file_name("other.jsonl")).unwrap();
+    let report = import(&mut other, &format!("{}{{\"expression\": \"\", \"target\": \"Integer\", \"result\": {{\"Integer\": \"1\"}}}}\nnot json\n", exported)).unwrap();
+    assert_eq!((report.version, report.imported, report.duplicates), (1, 2, 0));
+    assert_eq!(report.errors.iter().map(|error| error.line).collect::<Vec<usize>>(), vec![4, 5]);
+    assert_eq!(other.entries()[0].timestamp, history.entries()[0].timestamp);
+    assert_eq!(import(&mut other, "{\"version\": 9}"), Err("The file is of version 9 but only versions up to 1 can be imported".to_string()));
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+  }
+
+  #[test]
+  fn test_settings() {
+    use crate::{parser::{context::AngleUnit, format::FormatOptions}, settings::{Settings, Store}};
+    let path = std::env::temp_dir().join(format!("calcit-settings-{}", std::process::id())).join("settings.json");
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(Store::open(path.clone()).get(), &Settings::default());
+    let format = FormatOptions { decimals: Some(2), width: Some(8), signed: false, ..FormatOptions::default() };
+    let settings = Settings { target: Target::Hexadecimal, angle: AngleUnit::Degrees, format: format.clone(), ..Settings::default() };
+    Store::open(path.clone()).set(settings.clone()).unwrap();
+    assert_eq!(Store::open(path.clone()).get(), &settings);
+    //an unsigned byte shows -1 as 255
+    assert_eq!(Constant::Integer(-1).describe(&format).1, "255");
+    assert_eq!(Constant::Integer(200).describe(&FormatOptions { width: Some(8), ..FormatOptions::default() }).1, "-56");
+    //a file without a version is migrated, what does not fit is set back to its default
+    std::fs::write(&path, "{\"target\": \"Decimal\", \"angle\": \"Gradians\", \"format\": {\"grouping\": true}, \"theme\": \"dark\"}").unwrap();
+    let migrated = Store::open(path.clone()).get().clone();
+    assert_eq!((migrated.version, migrated.target, migrated.angle, migrated.format.grouping), (1, Target::Integer, AngleUnit::Gradians, true));
+    assert!(std::fs::read_to_string(&path).unwrap().contains("\"version\": 1"));
+    std::fs::write(&path, "{\"version\": 2, \"target\": \"Float\"}").unwrap();
+    assert_eq!(Store::open(path.clone()).get(), &Settings::default());
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+  }
+
+  #[test]
+  fn test_angles() {
+    use crate::parser::{calculate::Parser, context::{AngleUnit, Context}};
+    let calculate = |expression: &str, angle: AngleUnit| match Parser::new(expression).execute_in(Target::Float, &Context { angle, ..Context::default() }) {
+      Ok(Constant::Float(value)) => Ok((value * 1e9).round() / 1e9),
+      result => Err(format!("{:?}", result)),
+    };
+    assert_eq!(calculate("sin(90)", AngleUnit::Degrees), Ok(1.0));
+    assert_eq!(calculate("cos(pi)", AngleUnit::Radians), Ok(-1.0));
+    assert_eq!(calculate("sin(100)", AngleUnit::Gradians), Ok(1.0));
+    //a suffix gives the unit of a single value whatever the mode is
+    assert_eq!(calculate("sin(30deg)", AngleUnit::Radians), Ok(0.5));
+    assert_eq!(calculate("tan(50grad)", AngleUnit::Degrees), Ok(1.0));
+    assert_eq!(calculate("cos(1.2rad) - cos(1.2)", AngleUnit::Radians), Ok(0.0));
+    assert_eq!(calculate("30deg", AngleUnit::Degrees), Ok(30.0));
+    //inverse functions give their angle in the mode
+    assert_eq!(calculate("asin(1)", AngleUnit::Degrees), Ok(90.0));
+    assert_eq!(calculate("atan2(1, 1)", AngleUnit::Gradians), Ok(50.0));
+    assert_eq!(calculate("todeg(pi)", AngleUnit::Radians), Ok(180.0));
+    assert_eq!(calculate("tograd(180)", AngleUnit::Degrees), Ok(200.0));
+    //a unit called like a function is the same as its suffix
+    assert_eq!(calculate("sin(deg(30))", AngleUnit::Radians), Ok(0.5));
+    assert_eq!(calculate("deg(30) - 30deg", AngleUnit::Gradians), Ok(0.0));
+    assert_eq!(calculate("rad(pi)", AngleUnit::Degrees), Ok(180.0));
+    assert_eq!(calculate("ln(exp(2)) + sqrt(16)", AngleUnit::Degrees), Ok(6.0));
+    assert_eq!(calculate("asin(2)", AngleUnit::Radians), Err("Err(\"asin is not defined for 2\")".to_string()));
+  }
+
+  #[test]
+  fn test_multiline_input() {
+    use crate::parser::session::Session;
+    assert!(Session::is_complete("1 + 2"));
+    assert!(!Session::is_complete("f(x) = (\n  x * 2"));
+    assert!(!Session::is_complete("[1, 2,"));
+    assert!(Session::is_complete("f(x) = (\n  x * 2\n)"));
+    //brackets in a comment do not count
+    assert!(Session::is_complete("1 + 2 # (open"));
+    let mut session = Session::default();
+    let results = session.run_script("f(x) = (\n  x * 2\n)\nf(21)", Target::Integer);
+    assert_eq!(results[1].result, Ok(Constant::Integer(42)));
+  }
 }
@@ -1,23 +1,44 @@
 pub mod logger {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    static SILENT: AtomicBool = AtomicBool::new(false);
+
+    /**
+     Stops every message from being printed, for the command line where stdout holds the results.
+     */
+    pub fn silence() {
+        SILENT.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_silent() -> bool {
+        SILENT.load(Ordering::Relaxed)
+    }
+
     #[allow(unused)]
     macro_rules! debug {
         ($($t:tt)*) => {
             #[cfg(debug_assertions)]
             //print with color yellow
-            println!("\x1b[33m[DEBUG]: {}\x1b[0m", format!($($t)*));
+            if !$crate::logger::logger::is_silent() {
+                println!("\x1b[33m[DEBUG]: {}\x1b[0m", format!($($t)*));
+            }
         };
     }
     #[allow(unused)]
     macro_rules! info {
         ($($t:tt)*) => {
             //print with color blue
-            println!("\x1b[34m[INFO]: {}\x1b[0m", format!($($t)*));
+            if !$crate::logger::logger::is_silent() {
+                println!("\x1b[34m[INFO]: {}\x1b[0m", format!($($t)*));
+            }
         };
     }
     macro_rules! error {
         ($($t:tt)*) => {
             //print with color red
-            println!("\x1b[31m[ERROR]: {}\x1b[0m", format!($($t)*));
+            if !$crate::logger::logger::is_silent() {
+                println!("\x1b[31m[ERROR]: {}\x1b[0m", format!($($t)*));
+            }
         };
     }
     #[allow(unused)]
@@ -25,4 +46,4 @@ pub mod logger {
     #[allow(unused)]
     pub(crate) use info;
     pub(crate) use error;
-}
\ No newline at end of file
+}
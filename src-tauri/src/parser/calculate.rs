@@ -1,6 +1,57 @@
 use crate::logger::logger::{self, debug, error};
-use super::{tokens::{Token}, expressions::{EnumExpression, ConstExpression, UnaryExpression, BinaryExpression, Expression}, constants::{Target, Constant}};
+use super::{tokens::{Token}, expressions::{EnumExpression, ConstExpression, UnaryExpression, BinaryExpression, ConditionalExpression, CallExpression, ModularExpression, ListExpression, VariableExpression, LambdaExpression, Expression}, constants::{Target, Constant}, context::Context};
 use logos::{Logos, Lexer};
+use serde::{Deserialize, Serialize};
+
+/**
+ Number of precedence levels, the whole expression is wrapped in this many parenthesis.
+ */
+const PRECEDENCE_LEVELS: usize = 15;
+
+/**
+ The level of an operator, the parser pads it with this many parenthesis so a higher level binds looser.
+ * Symbolic results are written out with the same levels, so this is the only place they are set
+ */
+pub fn precedence(operator: &str) -> usize {
+    match operator {
+        "**" | "√" => 1,
+        "*" | "/" | "/." | "//" | "div" => 3,
+        "%" | "rem" | "mod" => 4,
+        "+" | "-" => 5,
+        "<<" | ">>" => 6,
+        "<" | "<=" | ">" | ">=" => 7,
+        "==" | "!=" => 8,
+        "&" | "|" => 9,
+        "^" => 10,
+        "&&" => 11,
+        "||" => 12,
+        "?" | ":" => 13,
+        ".." => 14,
+        _ => PRECEDENCE_LEVELS,
+    }
+}
+
+/**
+ How tightly implicit multiplication like 2x binds.
+ * Textbook binds it tighter than * and /, so 1/2x is 1/(2x)
+ * Strict treats it like *, so 1/2x is (1/2)x
+ */
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum Implicit {
+    #[default]
+    Textbook,
+    Strict,
+}
+
+impl Implicit {
+    fn level(self) -> usize {
+        match self {
+            //between ** and *
+            Implicit::Textbook => precedence("**") + 1,
+            Implicit::Strict => precedence("*"),
+        }
+    }
+}
 
 /**
  A parser that takes a string and returns a constant.
@@ -13,8 +64,15 @@ pub struct Parser<'a> {
 
 impl <'a> Parser <'a> {
     pub fn new(string: &'a str) -> Self {
+        Self::with_implicit(string, Implicit::default())
+    }
+
+    /**
+     A parser that reads implicit multiplication with the given convention
+     */
+    pub fn with_implicit(string: &'a str, implicit: Implicit) -> Self {
         debug!("Original: {}", string);
-        let precedence = Self::gen_precedence(string);
+        let precedence = Self::gen_precedence(string, implicit);
         let lexer = Token::lexer(precedence);
         let size = lexer.clone().spanned().count();
         Self {
@@ -24,18 +82,93 @@ impl <'a> Parser <'a> {
         }
     }
 
+    /**
+     Surrounds an operator with as many parenthesis as its precedence level, lower binds tighter.
+     */
+    fn pad(level: usize, operator: &str) -> String {
+        format!("{}{}{}", ")".repeat(level), operator, "(".repeat(level))
+    }
+
+    /**
+     Pads an operator at its own level.
+     */
+    fn pad_operator(operator: &str) -> String {
+        Self::pad(precedence(operator), operator)
+    }
+
+    /**
+     Closes the bodies of the lambdas that started at the given bracket depth or deeper.
+     */
+    fn close_lambdas(precedence: &mut String, lambdas: &mut Vec<usize>, depth: usize) {
+        while matches!(lambdas.last(), Some(start) if *start >= depth) {
+            lambdas.pop();
+            precedence.push_str(&")".repeat(PRECEDENCE_LEVELS));
+        }
+    }
+
+    /**
+     Reads a parameter list like the one of a lambda, the lexer has to be right after the opening parenthesis.
+     * Returns None when the parenthesis is not followed by names, a closing parenthesis and the follow token
+     * The caller skips the names, commas and closing parenthesis itself
+     */
+    pub fn parameters(lexer: &Lexer<Token>, follow: Token) -> Option<Vec<String>> {
+        let mut lexer = lexer.clone();
+        let mut parameters = Vec::new();
+        loop {
+            match lexer.next()?.ok()? {
+                Token::Identifier(name) => parameters.push(name),
+                Token::RightParenthesis if parameters.is_empty() => break,
+                _ => return None,
+            }
+            match lexer.next()?.ok()? {
+                Token::Comma => continue,
+                Token::RightParenthesis => break,
+                _ => return None,
+            }
+        }
+        if lexer.next()?.ok()? != follow {
+            return None;
+        }
+        Some(parameters)
+    }
+
+    /**
+     Whether the next token begins a value, a minus does not count so 10% - 5 subtracts and neither does the of of 15% of 200
+     */
+    fn followed_by_value(lexer: &Lexer<Token>) -> bool {
+        match lexer.clone().next() {
+            Some(Ok(Token::Identifier(name))) if name == "of" => false,
+            Some(Ok(token)) => token.starts_operand() || matches!(token, Token::Not | Token::Root | Token::LeftBracket),
+            _ => false,
+        }
+    }
+
     /**
      Generates a string with parenthesis to enforce operator precedence.
      
      Inspired by: https://en.wikipedia.org/wiki/Operator-precedence_parser#Precedence_climbing_method
      */
-    pub fn gen_precedence(string: &str) -> &'static str {
+    pub fn gen_precedence(string: &str, implicit: Implicit) -> &'static str {
         let mut lexer = Token::lexer(string);
-        let mut precedence = "((((((((".to_string();
+        let mut precedence = "(".repeat(PRECEDENCE_LEVELS);
         let mut next = lexer.next();
         let mut prev: Option<Token> = None;
+        //how many brackets are open, and the depth every unfinished lambda body started at
+        let mut depth = 0usize;
+        let mut lambdas: Vec<usize> = Vec::new();
         while let Some(r_token) = next.clone() {
             if let Ok(token) = r_token {
+                //a value right after a number or a closing parenthesis is multiplied, 2(3 + 4), 2pi and (a + b)(a - b)
+                let implied = match &prev {
+                    Some(Token::RightParenthesis) => token.starts_operand(),
+                    Some(prev) if prev.is_number() => token.starts_operand() && !token.is_number(),
+                    _ => false,
+                };
+                if implied {
+                    precedence.push_str(&Self::pad(implicit.level(), "*"));
+                }
+                //a % that no value follows is a percentage, 15% and 15% of 200, otherwise it is a remainder
+                let token = if token == Token::Modulo && !Self::followed_by_value(&lexer) { Token::Percent } else { token };
                 match token {
                     Token::Binary(b) => {
                         precedence.push_str(&format!("{}", b));
@@ -50,63 +183,185 @@ impl <'a> Parser <'a> {
                         precedence.push_str(&format!("{}", h));
                     },
                     Token::Float(f) => {
-                        precedence.push_str(&format!("{}", f));
+                        //2.0 has to stay a float when it is lexed again, and 1e20 is not a number the lexer reads
+                        let float = f.to_string();
+                        precedence.push_str(&if float.contains('.') { float } else { float + ".0" });
                     },
                     Token::Minus => {
-                        if prev.is_none() || !prev.as_ref().unwrap().is_number() {
+                        if prev.is_none() || !prev.as_ref().unwrap().ends_operand() {
                             precedence.push_str("-");
                         } else {
-                            precedence.push_str("))))-((((");
+                            precedence.push_str(&Self::pad_operator("-"));
                         }
                     },
                     Token::Not => {
                         precedence.push_str("!");
                     },
                     Token::LeftParenthesis => {
-                        precedence.push_str("((((((((");
+                        //the parameters of a lambda are kept as they are
+                        if let Some(parameters) = Self::parameters(&lexer, Token::Arrow) {
+                            for _ in 0..(2 * parameters.len()).max(1) {
+                                lexer.next();
+                            }
+                            precedence.push_str(&format!("({})", parameters.join(",")));
+                        } else {
+                            depth += 1;
+                            precedence.push_str(&"(".repeat(PRECEDENCE_LEVELS));
+                        }
                     },
                     Token::RightParenthesis => {
-                        precedence.push_str("))))))))");
+                        Self::close_lambdas(&mut precedence, &mut lambdas, depth);
+                        depth = depth.saturating_sub(1);
+                        precedence.push_str(&")".repeat(PRECEDENCE_LEVELS));
+                    },
+                    Token::LeftBrace => {
+                        depth += 1;
+                        precedence.push_str(&Self::pad(PRECEDENCE_LEVELS, "{"));
+                    },
+                    Token::RightBrace => {
+                        Self::close_lambdas(&mut precedence, &mut lambdas, depth);
+                        depth = depth.saturating_sub(1);
+                        precedence.push_str(&format!("{}}}", ")".repeat(PRECEDENCE_LEVELS)));
+                    },
+                    Token::LeftBracket => {
+                        depth += 1;
+                        precedence.push_str(&format!("[{}", "(".repeat(PRECEDENCE_LEVELS)));
+                    },
+                    Token::RightBracket => {
+                        Self::close_lambdas(&mut precedence, &mut lambdas, depth);
+                        depth = depth.saturating_sub(1);
+                        //an empty list has nothing to group, so take the parenthesis of the bracket back
+                        if prev == Some(Token::LeftBracket) {
+                            precedence.truncate(precedence.len() - PRECEDENCE_LEVELS);
+                            precedence.push(']');
+                        } else {
+                            precedence.push_str(&format!("{}]", ")".repeat(PRECEDENCE_LEVELS)));
+                        }
+                    },
+                    Token::Range => {
+                        precedence.push_str(&Self::pad_operator(".."));
+                    },
+                    Token::Arrow => {
+                        //the body of a lambda reaches until the comma or bracket that ends it
+                        lambdas.push(depth);
+                        precedence.push_str(&format!("->{}", "(".repeat(PRECEDENCE_LEVELS)));
+                    },
+                    Token::Assign => {
+                        //assignments are split off by the session, one left here can not be parsed
+                        precedence.push('=');
+                    },
+                    Token::Comma => {
+                        Self::close_lambdas(&mut precedence, &mut lambdas, depth);
+                        precedence.push_str(&Self::pad(PRECEDENCE_LEVELS, ","));
+                    },
+                    //of is only a word after a percentage, 15% of 200, anywhere else it is a name
+                    Token::Identifier(ref name) if name == "of" && prev == Some(Token::Percent) => {
+                        precedence.push_str(&Self::pad_operator("*"));
+                    },
+                    Token::Identifier(ref name) => {
+                        //the space keeps the name apart from whatever follows it
+                        precedence.push_str(&format!("{} ", name));
                     },
                     Token::Exponent => {
-                        precedence.push_str(")**(");
+                        precedence.push_str(&Self::pad_operator("**"));
                     },
                     Token::Root => {
-                        precedence.push_str(")//(");
+                        if prev.is_none() || !prev.as_ref().unwrap().ends_operand() {
+                            precedence.push('√');
+                        } else {
+                            precedence.push_str(&Self::pad_operator("√"));
+                        }
                     },
                     Token::Multiply => {
-                        precedence.push_str("))*((");
+                        precedence.push_str(&Self::pad_operator("*"));
                     },
                     Token::Divide => {
-                        precedence.push_str("))/((");
+                        precedence.push_str(&Self::pad_operator("/"));
+                    },
+                    Token::TrueDivide => {
+                        precedence.push_str(&Self::pad_operator("/."));
+                    },
+                    Token::FloorDivide => {
+                        precedence.push_str(&Self::pad_operator("//"));
+                    },
+                    Token::EuclideanDivide => {
+                        precedence.push_str(&Self::pad_operator("div"));
                     },
                     Token::Modulo => {
-                        precedence.push_str(")))%(((");
+                        precedence.push_str(&Self::pad_operator("%"));
+                    },
+                    Token::Percent => {
+                        precedence.push('%');
+                    },
+                    Token::Remainder => {
+                        precedence.push_str(&Self::pad_operator("rem"));
+                    },
+                    Token::EuclideanModulo => {
+                        //mod in front of a value starts a modular block, its modulus is a group of its own
+                        if prev.is_none() || !prev.as_ref().unwrap().ends_operand() {
+                            precedence.push_str(&format!("mod{}", "(".repeat(PRECEDENCE_LEVELS)));
+                        } else {
+                            precedence.push_str(&Self::pad_operator("mod"));
+                        }
                     },
                     Token::Plus => {
-                        precedence.push_str("))))+((((");
+                        precedence.push_str(&Self::pad_operator("+"));
                     },
                     Token::LeftShift => {
-                        precedence.push_str(")))))<<(((((");
+                        precedence.push_str(&Self::pad_operator("<<"));
                     },
                     Token::RightShift => {
-                        precedence.push_str(")))))>>(((((");
+                        precedence.push_str(&Self::pad_operator(">>"));
+                    },
+                    Token::Less => {
+                        precedence.push_str(&Self::pad_operator("<"));
+                    },
+                    Token::LessEqual => {
+                        precedence.push_str(&Self::pad_operator("<="));
+                    },
+                    Token::Greater => {
+                        precedence.push_str(&Self::pad_operator(">"));
+                    },
+                    Token::GreaterEqual => {
+                        precedence.push_str(&Self::pad_operator(">="));
+                    },
+                    Token::Equal => {
+                        precedence.push_str(&Self::pad_operator("=="));
+                    },
+                    Token::NotEqual => {
+                        precedence.push_str(&Self::pad_operator("!="));
                     },
                     Token::And => {
-                        precedence.push_str("))))))&((((((");
+                        precedence.push_str(&Self::pad_operator("&"));
                     },
                     Token::Or => {
-                        precedence.push_str("))))))|((((((");
+                        precedence.push_str(&Self::pad_operator("|"));
                     },
                     Token::Xor => {
-                        precedence.push_str(")))))))^(((((((");
+                        precedence.push_str(&Self::pad_operator("^"));
+                    },
+                    Token::LogicalAnd => {
+                        precedence.push_str(&Self::pad_operator("&&"));
+                    },
+                    Token::LogicalOr => {
+                        precedence.push_str(&Self::pad_operator("||"));
+                    },
+                    Token::Question => {
+                        precedence.push_str(&Self::pad_operator("?"));
+                    },
+                    Token::Colon => {
+                        precedence.push_str(&Self::pad_operator(":"));
                     },
                 }
                 prev = Some(token);
-                next = lexer.next();
+            } else {
+                //keep the unknown text so the parser reports it instead of looping forever
+                precedence.push_str(lexer.slice());
             }
+            next = lexer.next();
         }
-        precedence.push_str("))))))))");
+        Self::close_lambdas(&mut precedence, &mut lambdas, 0);
+        precedence.push_str(&")".repeat(PRECEDENCE_LEVELS));
         debug!("Precedence: {}", precedence);
         Box::leak(precedence.into_boxed_str())
     }
@@ -117,6 +372,31 @@ impl <'a> Parser <'a> {
      * Evaluates the expression tree
      */
     pub fn execute(&mut self, t: Target) -> Result<Constant, String> {
+        self.execute_in(t, &Context::default())
+    }
+    /**
+     Executes the parser with the given context
+     * A modulus in the context makes the whole expression modular, like wrapping it in mod N { ... }
+     */
+    pub fn execute_in(&mut self, t: Target, context: &Context) -> Result<Constant, String> {
+        let eval = self.evaluate_in(context)?.into_target_constant(t);
+        debug!("Evaluated expression: {:?}", serde_json::to_string(&eval).unwrap());
+        Ok(eval)
+    }
+    /**
+     Parses and evaluates the expression without converting the result to a target
+     */
+    pub fn evaluate_in(&mut self, context: &Context) -> Result<Constant, String> {
+        let expression = self.parse()?;
+        expression.evaluate(context).map_err(|err| {
+            logger::error!("{}", err);
+            err
+        })
+    }
+    /**
+     Parses the expression into an expression tree without evaluating it
+     */
+    pub fn parse(&mut self) -> Result<EnumExpression, String> {
         //find the first token
         //if the first token is a number or a unary operator, then insert it into the expression tree
         let expression = self.next_expression();
@@ -124,10 +404,110 @@ impl <'a> Parser <'a> {
             logger::error!("Could not parse the expression");
             return Err("Could not parse the expression".to_string());
         }
-        let expression = expression.unwrap();
-        let eval = expression.evaluate().into_target_constant(t);
-        debug!("Evaluated expression: {:?}", serde_json::to_string(&eval).unwrap());
-        Ok(eval)
+        Ok(expression.unwrap())
+    }
+    /**
+     Looks at the next token without consuming it
+     */
+    fn peek(&self) -> Option<Token> {
+        self.lexer.clone().next().and_then(|token| token.ok())
+    }
+    /**
+     Whether a percentage comes next, gen_precedence leaves a % that is one without a value after it
+     */
+    fn peek_percent(&self) -> bool {
+        let mut lexer = self.lexer.clone();
+        lexer.next() == Some(Ok(Token::Modulo)) && !Self::followed_by_value(&lexer)
+    }
+    /**
+     Parses the arguments of a function call, every argument is a group of its own separated by commas
+     */
+    fn next_arguments(&mut self) -> Option<Vec<EnumExpression>> {
+        let mut arguments = Vec::new();
+        loop {
+            let argument = self.next_value();
+            if argument.is_none() {
+                error!("Could not get the next argument");
+                return None;
+            }
+            arguments.push(argument.unwrap());
+            if self.peek() != Some(Token::Comma) {
+                return Some(arguments);
+            }
+            self.lexer.next();
+            self.current += 1;
+        }
+    }
+    /**
+     Parses the arrow and the body of a lambda whose parameters were already read
+     */
+    fn next_lambda(&mut self, parameters: Vec<String>) -> Option<EnumExpression> {
+        if self.lexer.next() != Some(Ok(Token::Arrow)) {
+            error!("Could not find the arrow of the lambda");
+            return None;
+        }
+        self.current += 1;
+        let body = self.next_value();
+        if body.is_none() {
+            error!("Could not get the body of the lambda");
+            return None;
+        }
+        Some(EnumExpression::Lambda(LambdaExpression::new(parameters, Box::new(body.unwrap()))))
+    }
+    /**
+     Parses what follows a name, a call when a parenthesis follows, a lambda when an arrow does and a variable otherwise
+     */
+    fn next_name(&mut self, name: String) -> Option<EnumExpression> {
+        if self.peek() == Some(Token::Arrow) {
+            return self.next_lambda(vec![name]);
+        }
+        if self.peek() != Some(Token::LeftParenthesis) {
+            return Some(EnumExpression::Variable(VariableExpression::new(name)));
+        }
+        let arguments = self.next_arguments();
+        if arguments.is_none() {
+            error!("Could not get the arguments of {}", name);
+            return None;
+        }
+        Some(EnumExpression::Call(CallExpression::new(name, arguments.unwrap())))
+    }
+    /**
+     Parses the modulus and the block of mod N { ... }, the mod itself was already read
+     */
+    fn next_modular(&mut self) -> Option<EnumExpression> {
+        let modulus = self.next_value();
+        if modulus.is_none() {
+            error!("Could not get the modulus");
+            return None;
+        }
+        if self.lexer.next() != Some(Ok(Token::LeftBrace)) {
+            error!("Could not find the block of the modulus");
+            return None;
+        }
+        self.current += 1;
+        let block = self.next_value();
+        if block.is_none() || self.lexer.next() != Some(Ok(Token::RightBrace)) {
+            error!("Could not find the end of the modular block");
+            return None;
+        }
+        self.current += 1;
+        Some(EnumExpression::Modular(ModularExpression::new(Box::new(modulus.unwrap()), Box::new(block.unwrap()))))
+    }
+    /**
+     Parses the items of a list up to its closing bracket, the opening bracket was already read
+     */
+    fn next_list(&mut self) -> Option<EnumExpression> {
+        let items = if self.peek() == Some(Token::RightBracket) {
+            Some(Vec::new())
+        } else {
+            self.next_arguments()
+        };
+        if items.is_none() || self.lexer.next() != Some(Ok(Token::RightBracket)) {
+            error!("Could not find the end of the list");
+            return None;
+        }
+        self.current += 1;
+        Some(EnumExpression::List(ListExpression::new(items.unwrap())))
     }
     /**
      Creates constant expressions from the tokens
@@ -168,7 +548,33 @@ impl <'a> Parser <'a> {
                 }
                 expression = Some(EnumExpression::Unary(UnaryExpression::new(Box::new(next_expression.unwrap()), "!")));
             },
+            Token::Root => {
+                //a root without an index is a square root
+                let next_expression = self.next_value();
+                if next_expression.is_none() {
+                    error!("Could not get the next expression");
+                    return None;
+                }
+                let index = EnumExpression::Constant(ConstExpression::new(Token::Integer(2)));
+                expression = Some(EnumExpression::Binary(BinaryExpression::new(Box::new(next_expression.unwrap()), Box::new(index), "√")));
+            },
+            Token::Identifier(name) => {
+                expression = self.next_name(name);
+            },
+            Token::EuclideanModulo => {
+                expression = self.next_modular();
+            },
+            Token::LeftBracket => {
+                expression = self.next_list();
+            },
             Token::LeftParenthesis => {
+                if let Some(parameters) = Self::parameters(&self.lexer, Token::Arrow) {
+                    for _ in 0..(2 * parameters.len()).max(1) {
+                        self.lexer.next();
+                        self.current += 1;
+                    }
+                    return self.next_lambda(parameters);
+                }
                 let next_expression = self.next_expression();
                 if next_expression.is_none() {
                     error!("Could not get the next expression");
@@ -185,75 +591,105 @@ impl <'a> Parser <'a> {
                 return None;
             }
         }
+        //percentages follow their value and bind tighter than any operator
+        while expression.is_some() && self.peek_percent() {
+            self.lexer.next();
+            self.current += 1;
+            expression = Some(EnumExpression::Unary(UnaryExpression::new(Box::new(expression.unwrap()), "%")));
+        }
         expression
     }
     /**
-     Recursively finds the next expression
+     The operator a binary token stands for in the expression tree
+     */
+    fn operator(token: &Token) -> Option<&'static str> {
+        Some(match token {
+            Token::Exponent => "**",
+            Token::Root => "√",
+            Token::Multiply => "*",
+            Token::Divide => "/",
+            Token::TrueDivide => "/.",
+            Token::FloorDivide => "//",
+            Token::EuclideanDivide => "div",
+            Token::Modulo => "%",
+            Token::Remainder => "rem",
+            Token::EuclideanModulo => "mod",
+            Token::Plus => "+",
+            Token::Minus => "-",
+            Token::LeftShift => "<<",
+            Token::RightShift => ">>",
+            Token::And => "&",
+            Token::Or => "|",
+            Token::Xor => "^",
+            Token::Equal => "==",
+            Token::NotEqual => "!=",
+            Token::Less => "<",
+            Token::LessEqual => "<=",
+            Token::Greater => ">",
+            Token::GreaterEqual => ">=",
+            Token::LogicalAnd => "&&",
+            Token::LogicalOr => "||",
+            Token::Range => "..",
+            _ => return None,
+        })
+    }
+    /**
+     Finds the next expression up to the parenthesis that closes its group
+     * Every operator inside a group has the same precedence, so they are folded from left to right
+     * Exponents and roots are the exception and group from right to left
      */
     pub fn next_expression(&mut self) -> Option<EnumExpression> {
-        #[allow(unused_assignments)]
-        let mut expression: Option<EnumExpression> = None;
-        let left = self.next_value();
+        let mut left = self.next_value();
         if left.is_none() {
             error!("Could not get the next value");
             return None;
         }
-        let next = self.lexer.next();
-        if next.is_none() {
-            return left;
-        }
-        let next = next.unwrap();
-        if next.is_err() {
-            return left;
-        }
-        let next = next.unwrap();
-        self.current += 1;
-        if next == Token::RightParenthesis {
-            return left;
-        }
-        let right = self.next_expression();
-        match next {
-            Token::Exponent => {
-                expression = Some(EnumExpression::Binary(BinaryExpression::new(Box::new(left.unwrap()), Box::new(right.unwrap()), "**")));
-            },
-            Token::Root => {
-                expression = Some(EnumExpression::Binary(BinaryExpression::new(Box::new(left.unwrap()), Box::new(right.unwrap()), "//")));
-            },
-            Token::Multiply => {
-                expression = Some(EnumExpression::Binary(BinaryExpression::new(Box::new(left.unwrap()), Box::new(right.unwrap()), "*")));
-            },
-            Token::Divide => {
-                expression = Some(EnumExpression::Binary(BinaryExpression::new(Box::new(left.unwrap()), Box::new(right.unwrap()), "/")));
-            },
-            Token::Modulo => {
-                expression = Some(EnumExpression::Binary(BinaryExpression::new(Box::new(left.unwrap()), Box::new(right.unwrap()), "%")));
-            },
-            Token::Plus => {
-                expression = Some(EnumExpression::Binary(BinaryExpression::new(Box::new(left.unwrap()), Box::new(right.unwrap()), "+")));
-            },
-            Token::Minus => {
-                expression = Some(EnumExpression::Binary(BinaryExpression::new(Box::new(left.unwrap()), Box::new(right.unwrap()), "-")));
-            },
-            Token::LeftShift => {
-                expression = Some(EnumExpression::Binary(BinaryExpression::new(Box::new(left.unwrap()), Box::new(right.unwrap()), "<<")));
-            },
-            Token::RightShift => {
-                expression = Some(EnumExpression::Binary(BinaryExpression::new(Box::new(left.unwrap()), Box::new(right.unwrap()), ">>")));
-            },
-            Token::And => {
-                expression = Some(EnumExpression::Binary(BinaryExpression::new(Box::new(left.unwrap()), Box::new(right.unwrap()), "&")));
-            },
-            Token::Or => {
-                expression = Some(EnumExpression::Binary(BinaryExpression::new(Box::new(left.unwrap()), Box::new(right.unwrap()), "|")));
-            },
-            Token::Xor => {
-                expression = Some(EnumExpression::Binary(BinaryExpression::new(Box::new(left.unwrap()), Box::new(right.unwrap()), "^")));
-            },
-            _ => {
+        loop {
+            let next = self.lexer.next();
+            if next.is_none() {
+                return left;
+            }
+            let next = next.unwrap();
+            if next.is_err() {
+                return left;
+            }
+            let next = next.unwrap();
+            self.current += 1;
+            //a colon closes the consequent of a conditional the same way a parenthesis closes a group
+            if next == Token::RightParenthesis || next == Token::Colon {
+                return left;
+            }
+            if next == Token::Question {
+                let consequent = self.next_expression();
+                if consequent.is_none() || self.lexer.slice() != ":" {
+                    error!("Could not find the colon of the conditional");
+                    return None;
+                }
+                let alternative = self.next_expression();
+                if alternative.is_none() {
+                    error!("Could not get the alternative of the conditional");
+                    return None;
+                }
+                return Some(EnumExpression::Conditional(ConditionalExpression::new(Box::new(left.unwrap()), Box::new(consequent.unwrap()), Box::new(alternative.unwrap()))));
+            }
+            let operator = Self::operator(&next);
+            if operator.is_none() {
                 error!("Could not parse the expression");
                 return None;
             }
+            let right_to_left = next == Token::Exponent || next == Token::Root;
+            //the rest of the group is the right side, including the parenthesis that closes it
+            let right = if right_to_left { self.next_expression() } else { self.next_value() };
+            if right.is_none() {
+                error!("Could not get the right side of the expression");
+                return None;
+            }
+            //the index of a root is written before the root sign and the radicand after it
+            let (a, b) = if next == Token::Root { (right, left) } else { (left, right) };
+            left = Some(EnumExpression::Binary(BinaryExpression::new(Box::new(a.unwrap()), Box::new(b.unwrap()), operator.unwrap())));
+            if right_to_left {
+                return left;
+            }
         }
-        expression
     }
 }
\ No newline at end of file
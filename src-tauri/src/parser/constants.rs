@@ -4,20 +4,28 @@ use serde::{Deserialize, Serialize};
 
 use crate::logger::logger;
 
-use super::tokens::Token;
+use super::{calculate::Parser, expressions::{EnumExpression, Lambda}, format::{self, FormatOptions}, tokens::Token};
 
-#[derive(Clone, Copy, PartialEq, Debug)]
+#[derive(Clone, PartialEq, Debug)]
 pub enum Constant {
     Binary(i64),
     Integer(i64),
     Float(f64),
     Hexadecimal(i64),
     Octal(i64),
+    Boolean(bool),
+    //prime factorization as (prime, exponent) pairs, a negative number starts with (-1, 1)
+    Factors(Vec<(i64, u32)>),
+    List(Vec<Constant>),
+    Matrix(Vec<Vec<f64>>),
+    Function(Lambda),
+    //a symbolic result, like the derivative from diff
+    Expression(Box<EnumExpression>),
     #[allow(unused)]
     None,
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
 pub enum Target {
     #[allow(unused)]
     Integer = 0,
@@ -29,6 +37,8 @@ pub enum Target {
     Hexadecimal = 3,
     #[allow(unused)]
     Octal = 4,
+    #[allow(unused)]
+    Boolean = 5,
 }
 
 //custom deserializer for Constant to serialize hex, binary, and octal numbers from string to int
@@ -37,43 +47,12 @@ impl<'de> Deserialize<'de> for Constant {
     where
         D: serde::Deserializer<'de>,
     {
-        let mut map = HashMap::<&str, String>::deserialize(deserializer)?;
-        let mut constant = Constant::None;
-        for (key, value) in map.drain() {
-            match key {
-                "Binary" => {
-                    let s = value.trim_start_matches("0b");
-                    let i = i64::from_str_radix(s, 2).unwrap();
-                    constant = Constant::Binary(i);
-                },
-                "Integer" => {
-                    let i = value.parse::<i64>().unwrap();
-                    constant = Constant::Integer(i);
-                },
-                "Float" => {
-                    let f = value.parse::<f64>().unwrap();
-                    constant = Constant::Float(f);
-                },
-                "Hexadecimal" => {
-                    let s = value.trim_start_matches("0x");
-                    let i = i64::from_str_radix(s, 16).unwrap();
-                    constant = Constant::Hexadecimal(i);
-                },
-                "Octal" => {
-                    let s = value.trim_start_matches("0o");
-                    let i = i64::from_str_radix(s, 8).unwrap();
-                    constant = Constant::Octal(i);
-                },
-                "None" => {
-                    constant = Constant::None;
-                },
-                _ => {
-                    logger::error!("Error: Unknown key: {}", key);
-                    constant = Constant::None;
-                },
-            }
+        let map = HashMap::<String, String>::deserialize(deserializer)?;
+        if map.len() != 1 {
+            return Err(serde::de::Error::custom(format!("A constant needs exactly 1 kind but got {}", map.len())));
         }
-        Ok(constant)
+        let (key, value) = map.into_iter().next().unwrap_or_default();
+        Constant::read(&key, &value).map_err(serde::de::Error::custom)
     }
 }
 
@@ -83,37 +62,126 @@ impl Serialize for Constant {
         S: serde::Serializer,
     {
         let mut map = HashMap::<&str, String>::new();
+        let (key, s) = self.describe(&FormatOptions::default());
+        map.insert(key, s);
+        map.serialize(serializer)
+    }
+}
+
+impl Constant {
+    /**
+     The name of the variant and the text it is shown as with the given options, lists show their items the same way.
+     */
+    pub fn describe(&self, options: &FormatOptions) -> (&'static str, String) {
         match self {
-            Constant::Binary(i) => {
-                let s = format!("0b{:b}", i);
-                map.insert("Binary", s);
-            },
-            Constant::Integer(i) => {
-                let s = format!("{}", i);
-                map.insert("Integer", s);
+            Constant::Binary(i) => ("Binary", format::radix(*i, 2, options)),
+            Constant::Integer(i) => ("Integer", format::integer(*i, options)),
+            Constant::Float(f) => ("Float", format::float(*f, options)),
+            Constant::Hexadecimal(i) => ("Hexadecimal", format::radix(*i, 16, options)),
+            Constant::Octal(i) => ("Octal", format::radix(*i, 8, options)),
+            Constant::Boolean(b) => ("Boolean", format!("{}", b)),
+            Constant::Factors(factors) => {
+                let s = if factors.is_empty() {
+                    "1".to_string()
+                } else {
+                    factors.iter().map(|(prime, exponent)| {
+                        if *exponent == 1 { format!("{}", prime) } else { format!("{}**{}", prime, exponent) }
+                    }).collect::<Vec<String>>().join(" * ")
+                };
+                ("Factors", s)
             },
-            Constant::Float(f) => {
-                let s = format!("{:.}", f);
-                map.insert("Float", s);
+            Constant::List(items) => {
+                let s = items.iter().map(|item| item.describe(options).1).collect::<Vec<String>>().join(", ");
+                ("List", format!("[{}]", s))
             },
-            Constant::Hexadecimal(i) => {
-                let s = format!("0x{:x}", i);
-                map.insert("Hexadecimal", s);
+            Constant::Matrix(rows) => {
+                let s = rows.iter().map(|row| {
+                    format!("[{}]", row.iter().map(|i| format::float(*i, options)).collect::<Vec<String>>().join(", "))
+                }).collect::<Vec<String>>().join(", ");
+                ("Matrix", format!("[{}]", s))
             },
-            Constant::Octal(i) => {
-                let s = format!("0o{:o}", i);
-                map.insert("Octal", s);
+            Constant::Function(lambda) => ("Function", format!("({}) -> {}", lambda.parameters.join(", "), lambda.body)),
+            Constant::Expression(expression) => ("Expression", expression.to_string()),
+            Constant::None => ("None", "None".to_string()),
+        }
+    }
+    /**
+     Reads a constant back from its kind and the text describe gives it, anything that does not fit the kind is an error.
+     */
+    pub fn read(key: &str, value: &str) -> Result<Constant, String> {
+        let invalid = || format!("{} is not a valid {}", value, key);
+        Ok(match key {
+            "Binary" => Constant::Binary(Constant::read_radix(value, "0b", 2).ok_or_else(invalid)?),
+            "Integer" => Constant::Integer(value.parse::<i64>().map_err(|_| invalid())?),
+            "Float" => Constant::Float(value.parse::<f64>().map_err(|_| invalid())?),
+            "Hexadecimal" => Constant::Hexadecimal(Constant::read_radix(value, "0x", 16).ok_or_else(invalid)?),
+            "Octal" => Constant::Octal(Constant::read_radix(value, "0o", 8).ok_or_else(invalid)?),
+            "Boolean" => Constant::Boolean(value.parse::<bool>().map_err(|_| invalid())?),
+            "Factors" => {
+                let mut factors = Vec::new();
+                for factor in value.split(" * ").map(str::trim).filter(|f| !f.is_empty() && *f != "1") {
+                    let (prime, exponent) = factor.split_once("**").unwrap_or((factor, "1"));
+                    factors.push((prime.parse::<i64>().map_err(|_| invalid())?, exponent.parse::<u32>().map_err(|_| invalid())?));
+                }
+                Constant::Factors(factors)
             },
-            Constant::None => {
-                let s = format!("None");
-                map.insert("None", s);
+            "List" => Constant::parse_list(value).ok_or_else(invalid)?,
+            "Matrix" => match Constant::parse_list(value) {
+                Some(Constant::List(rows)) => Constant::Matrix(rows.into_iter().map(|row| match row {
+                    Constant::List(row) if row.iter().all(Constant::is_number) => Ok(row.into_iter().map(|i| i.into()).collect()),
+                    _ => Err(invalid()),
+                }).collect::<Result<Vec<Vec<f64>>, String>>()?),
+                _ => return Err(invalid()),
             },
+            "Expression" => Constant::Expression(Box::new(Parser::new(value).parse().map_err(|_| invalid())?)),
+            "Function" => return Err("A function can not be read back".to_string()),
+            "None" => Constant::None,
+            _ => return Err(format!("Unknown kind of constant: {}", key)),
+        })
+    }
+    /**
+     Reads a binary, hexadecimal or octal number, which describe writes in two's complement.
+     */
+    fn read_radix(value: &str, prefix: &str, radix: u32) -> Option<i64> {
+        u64::from_str_radix(value.trim_start_matches(prefix), radix).ok().map(|i| i as i64)
+    }
+    /**
+     Reads a list back from the text describe gives it, the prefix of every item decides its kind.
+     */
+    fn parse_list(s: &str) -> Option<Constant> {
+        let inner = s.trim().strip_prefix('[')?.strip_suffix(']')?;
+        let mut items = Vec::new();
+        let (mut depth, mut start) = (0, 0);
+        for (i, c) in inner.char_indices() {
+            match c {
+                '[' => depth += 1,
+                ']' => depth -= 1,
+                ',' if depth == 0 => {
+                    items.push(&inner[start..i]);
+                    start = i + 1;
+                },
+                _ => {},
+            }
         }
-        map.serialize(serializer)
+        items.push(&inner[start..]);
+        items.into_iter().map(str::trim).filter(|item| !item.is_empty()).map(|item| {
+            if item.starts_with('[') {
+                Constant::parse_list(item)
+            } else if item.starts_with("0b") {
+                Constant::read_radix(item, "0b", 2).map(Constant::Binary)
+            } else if item.starts_with("0x") {
+                Constant::read_radix(item, "0x", 16).map(Constant::Hexadecimal)
+            } else if item.starts_with("0o") {
+                Constant::read_radix(item, "0o", 8).map(Constant::Octal)
+            } else if let Ok(b) = item.parse::<bool>() {
+                Some(Constant::Boolean(b))
+            } else if let Ok(i) = item.parse::<i64>() {
+                Some(Constant::Integer(i))
+            } else {
+                item.parse::<f64>().ok().map(Constant::Float)
+            }
+        }).collect::<Option<Vec<Constant>>>().map(Constant::List)
     }
-}
-
-impl Constant {
     pub fn from_tok(t: Token) -> Self {
         match t {
             Token::Binary(i) => Self::Binary(i),
@@ -128,6 +196,13 @@ impl Constant {
         }
     }
     pub fn into_target_constant(self, target: Target) -> Constant {
+        //factorizations, matrices, functions and expressions are shown as they are whatever the target
+        if let Constant::Factors(_) | Constant::Matrix(_) | Constant::Function(_) | Constant::Expression(_) = self {
+            return self;
+        }
+        if let Constant::List(items) = self {
+            return Constant::List(items.into_iter().map(|item| item.into_target_constant(target)).collect());
+        }
         match target {
             Target::Integer => {
                 let i = self.into();
@@ -149,6 +224,50 @@ impl Constant {
                 let i = self.into();
                 Constant::Octal(i)
             },
+            Target::Boolean => {
+                Constant::Boolean(self.is_truthy())
+            },
+        }
+    }
+    /**
+     Whether the constant is a single number that arithmetic can work on.
+     */
+    pub fn is_number(&self) -> bool {
+        !matches!(self, Self::List(_) | Self::Matrix(_) | Self::Function(_) | Self::Expression(_) | Self::None)
+    }
+    /**
+     Whether the constant counts as true in a condition, any non-zero value is true.
+     */
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Self::Binary(i) | Self::Integer(i) | Self::Hexadecimal(i) | Self::Octal(i) => *i != 0,
+            Self::Float(f) => *f != 0.0,
+            Self::Boolean(b) => *b,
+            Self::Factors(_) => true,
+            Self::List(items) => !items.is_empty(),
+            Self::Matrix(_) | Self::Function(_) | Self::Expression(_) => true,
+            Self::None => false,
+        }
+    }
+    /**
+     Booleans take part in arithmetic as 1 and 0 and factorizations as their product, everything else is left untouched.
+     */
+    pub fn numeric(self) -> Constant {
+        match self {
+            Self::Boolean(b) => Self::Integer(b as i64),
+            Self::Factors(_) => Self::Integer(self.into()),
+            _ => self,
+        }
+    }
+    /**
+     Creates an integer of the same kind as this constant, anything that is not an integer kind gives an Integer.
+     */
+    pub fn with_value(&self, i: i64) -> Constant {
+        match self {
+            Self::Binary(_) => Self::Binary(i),
+            Self::Hexadecimal(_) => Self::Hexadecimal(i),
+            Self::Octal(_) => Self::Octal(i),
+            _ => Self::Integer(i),
         }
     }
 }
@@ -164,6 +283,8 @@ impl Into<i64> for Constant {
             },
             Self::Hexadecimal(i) => i,
             Self::Octal(i) => i,
+            Self::Boolean(b) => b as i64,
+            Self::Factors(factors) => factors.iter().fold(1i64, |product, (prime, exponent)| product.wrapping_mul(prime.wrapping_pow(*exponent))),
             _ => {
                 logger::error!("Could not convert Constant into i64");
                 0
@@ -180,6 +301,8 @@ impl Into<f64> for Constant {
             Self::Float(f) => f,
             Self::Hexadecimal(i) => i as f64,
             Self::Octal(i) => i as f64,
+            Self::Boolean(b) => b as i64 as f64,
+            Self::Factors(_) => Into::<i64>::into(self) as f64,
             _ => {
                 logger::error!("Could not convert Constant into f64");
                 0.0
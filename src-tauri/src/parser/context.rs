@@ -0,0 +1,67 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::constants::Constant;
+
+/**
+ The unit trigonometric functions read angles in and their inverses give them in.
+ */
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum AngleUnit {
+    #[default]
+    Radians,
+    Degrees,
+    Gradians,
+}
+
+impl AngleUnit {
+    /**
+     The size of one of these units in radians.
+     */
+    pub fn radians(self) -> f64 {
+        match self {
+            AngleUnit::Radians => 1.0,
+            AngleUnit::Degrees => std::f64::consts::PI / 180.0,
+            AngleUnit::Gradians => std::f64::consts::PI / 200.0,
+        }
+    }
+}
+
+/**
+ Settings and state that evaluation depends on, handed down the expression tree.
+ */
+#[derive(Clone, Debug, Default)]
+pub struct Context {
+    /**
+     When set every intermediate result of +, -, *, / and ** is reduced modulo this number
+     */
+    pub modulus: Option<i64>,
+    /**
+     Values that names stand for, lambda parameters and the variables a lambda captured
+     */
+    pub variables: HashMap<String, Constant>,
+    /**
+     The unit trigonometric functions read angles in and their inverses give them in
+     */
+    pub angle: AngleUnit,
+}
+
+impl Context {
+    /**
+     Same context with the given modulus, used by mod N { ... }
+     */
+    pub fn with_modulus(&self, modulus: i64) -> Self {
+        let mut context = self.clone();
+        context.modulus = Some(modulus);
+        context
+    }
+    /**
+     Same context without a modulus, exponents are counted normally even inside mod N { ... }
+     */
+    pub fn without_modulus(&self) -> Self {
+        let mut context = self.clone();
+        context.modulus = None;
+        context
+    }
+}
@@ -1,8 +1,15 @@
-use super::{constants::{Constant}, tokens::Token};
+use std::{cmp::Ordering, collections::HashMap, sync::Arc};
+
+use super::{constants::{Constant}, context::{AngleUnit, Context}, functions, matrix, number, tokens::Token};
 use serde::{Deserialize, Serialize};
 
+/**
+ * Longest list a range can make, so a typo like 1..10**12 does not eat all memory
+ */
+const RANGE_LIMIT: u64 = 1_000_000;
+
 pub trait Expression {
-    fn evaluate(&self) -> Constant;
+    fn evaluate(&self, context: &Context) -> Result<Constant, String>;
 }
 
 pub trait Unary {
@@ -13,14 +20,14 @@ pub trait Binary {
     fn new(left: Box<EnumExpression>, right: Box<EnumExpression>) -> Self;
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct ConstExpression {
     pub constant: Constant,
 }
 
 impl Expression for ConstExpression {
-    fn evaluate(&self) -> Constant {
-        self.constant
+    fn evaluate(&self, _context: &Context) -> Result<Constant, String> {
+        Ok(self.constant.clone())
     }
 }
 
@@ -32,18 +39,28 @@ impl ConstExpression {
 /**
  * Subtraction expression (left - right) implementation
  */
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct SubtractExpression {
     pub left: Box<EnumExpression>,
     pub right: Box<EnumExpression>,
 }
 
 impl Expression for SubtractExpression {
-    fn evaluate(&self) -> Constant {
-        let left = self.left.evaluate();
-        let right = self.right.evaluate();
+    fn evaluate(&self, context: &Context) -> Result<Constant, String> {
+        let left = self.left.evaluate(context)?.numeric();
+        //80 - 10% takes ten percent of 80 off
+        if let EnumExpression::Unary(UnaryExpression::Percent(percent)) = self.right.as_ref() {
+            return percent.change(left, -1.0, context);
+        }
+        let right = self.right.evaluate(context)?.numeric();
+        if let Some(result) = matrix::combine(&left, &right, "-")? {
+            return Ok(result);
+        }
+        if let Some(result) = modular(context, &left, &right, |a, b, _| Ok(a - b))? {
+            return Ok(result);
+        }
         //match the left and right values, left value holds precedence
-        match (left, right) {
+        Ok(match (left, right) {
             (Constant::Integer(left), Constant::Float(right)) => Constant::Float(left as f64 - right),
             (Constant::Binary(left), Constant::Float(right)) => Constant::Float(left as f64 - right),
             (Constant::Hexadecimal(left), Constant::Float(right)) => Constant::Float(left as f64 - right),
@@ -52,24 +69,25 @@ impl Expression for SubtractExpression {
             (Constant::Float(left), Constant::Binary(right)) => Constant::Float(left - right as f64),
             (Constant::Float(left), Constant::Hexadecimal(right)) => Constant::Float(left - right as f64),
             (Constant::Float(left), Constant::Octal(right)) => Constant::Float(left - right as f64),
-            (Constant::Integer(left), Constant::Integer(right)) => Constant::Integer(left - right),
-            (Constant::Binary(left), Constant::Integer(right)) => Constant::Integer(left - right),
-            (Constant::Hexadecimal(left), Constant::Integer(right)) => Constant::Integer(left - right),
-            (Constant::Octal(left), Constant::Integer(right)) => Constant::Integer(left - right),
-            (Constant::Integer(left), Constant::Binary(right)) => Constant::Integer(left - right),
-            (Constant::Binary(left), Constant::Binary(right)) => Constant::Binary(left - right),
-            (Constant::Hexadecimal(left), Constant::Binary(right)) => Constant::Binary(left - right),
-            (Constant::Octal(left), Constant::Binary(right)) => Constant::Binary(left - right),
-            (Constant::Integer(left), Constant::Hexadecimal(right)) => Constant::Integer(left - right),
-            (Constant::Binary(left), Constant::Hexadecimal(right)) => Constant::Binary(left - right),
-            (Constant::Hexadecimal(left), Constant::Hexadecimal(right)) => Constant::Hexadecimal(left - right),
-            (Constant::Octal(left), Constant::Hexadecimal(right)) => Constant::Hexadecimal(left - right),
-            (Constant::Integer(left), Constant::Octal(right)) => Constant::Integer(left - right),
-            (Constant::Binary(left), Constant::Octal(right)) => Constant::Binary(left - right),
-            (Constant::Hexadecimal(left), Constant::Octal(right)) => Constant::Hexadecimal(left - right),
-            (Constant::Octal(left), Constant::Octal(right)) => Constant::Octal(left - right),
-            _ => panic!("Invalid subtraction operation"),
-        }
+            (Constant::Float(left), Constant::Float(right)) => Constant::Float(left - right),
+            (Constant::Integer(left), Constant::Integer(right)) => Constant::Integer(checked(left.checked_sub(right))?),
+            (Constant::Binary(left), Constant::Integer(right)) => Constant::Integer(checked(left.checked_sub(right))?),
+            (Constant::Hexadecimal(left), Constant::Integer(right)) => Constant::Integer(checked(left.checked_sub(right))?),
+            (Constant::Octal(left), Constant::Integer(right)) => Constant::Integer(checked(left.checked_sub(right))?),
+            (Constant::Integer(left), Constant::Binary(right)) => Constant::Integer(checked(left.checked_sub(right))?),
+            (Constant::Binary(left), Constant::Binary(right)) => Constant::Binary(checked(left.checked_sub(right))?),
+            (Constant::Hexadecimal(left), Constant::Binary(right)) => Constant::Binary(checked(left.checked_sub(right))?),
+            (Constant::Octal(left), Constant::Binary(right)) => Constant::Binary(checked(left.checked_sub(right))?),
+            (Constant::Integer(left), Constant::Hexadecimal(right)) => Constant::Integer(checked(left.checked_sub(right))?),
+            (Constant::Binary(left), Constant::Hexadecimal(right)) => Constant::Binary(checked(left.checked_sub(right))?),
+            (Constant::Hexadecimal(left), Constant::Hexadecimal(right)) => Constant::Hexadecimal(checked(left.checked_sub(right))?),
+            (Constant::Octal(left), Constant::Hexadecimal(right)) => Constant::Hexadecimal(checked(left.checked_sub(right))?),
+            (Constant::Integer(left), Constant::Octal(right)) => Constant::Integer(checked(left.checked_sub(right))?),
+            (Constant::Binary(left), Constant::Octal(right)) => Constant::Binary(checked(left.checked_sub(right))?),
+            (Constant::Hexadecimal(left), Constant::Octal(right)) => Constant::Hexadecimal(checked(left.checked_sub(right))?),
+            (Constant::Octal(left), Constant::Octal(right)) => Constant::Octal(checked(left.checked_sub(right))?),
+            _ => return Err("Invalid subtraction operation".to_string()),
+        })
     }
 }
 
@@ -81,18 +99,28 @@ impl Binary for SubtractExpression {
 /**
  * Addition expression (left + right) implementation
  */
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct AddExpression {
     pub left: Box<EnumExpression>,
     pub right: Box<EnumExpression>,
 }
 
 impl Expression for AddExpression {
-    fn evaluate(&self) -> Constant {
-        let left = self.left.evaluate();
-        let right = self.right.evaluate();
+    fn evaluate(&self, context: &Context) -> Result<Constant, String> {
+        let left = self.left.evaluate(context)?.numeric();
+        //80 + 10% adds ten percent of 80
+        if let EnumExpression::Unary(UnaryExpression::Percent(percent)) = self.right.as_ref() {
+            return percent.change(left, 1.0, context);
+        }
+        let right = self.right.evaluate(context)?.numeric();
+        if let Some(result) = matrix::combine(&left, &right, "+")? {
+            return Ok(result);
+        }
+        if let Some(result) = modular(context, &left, &right, |a, b, _| Ok(a + b))? {
+            return Ok(result);
+        }
         //match the left and right values, left value holds precedence
-        match (left, right) {
+        Ok(match (left, right) {
             (Constant::Integer(left), Constant::Float(right)) => Constant::Float(left as f64 + right),
             (Constant::Binary(left), Constant::Float(right)) => Constant::Float(left as f64 + right),
             (Constant::Hexadecimal(left), Constant::Float(right)) => Constant::Float(left as f64 + right),
@@ -102,24 +130,24 @@ impl Expression for AddExpression {
             (Constant::Float(left), Constant::Hexadecimal(right)) => Constant::Float(left + right as f64),
             (Constant::Float(left), Constant::Octal(right)) => Constant::Float(left + right as f64),
             (Constant::Float(left), Constant::Float(right)) => Constant::Float(left + right),
-            (Constant::Integer(left), Constant::Integer(right)) => Constant::Integer(left + right),
-            (Constant::Binary(left), Constant::Integer(right)) => Constant::Integer(left + right),
-            (Constant::Hexadecimal(left), Constant::Integer(right)) => Constant::Integer(left + right),
-            (Constant::Octal(left), Constant::Integer(right)) => Constant::Integer(left + right),
-            (Constant::Integer(left), Constant::Binary(right)) => Constant::Integer(left + right),
-            (Constant::Binary(left), Constant::Binary(right)) => Constant::Binary(left + right),
-            (Constant::Hexadecimal(left), Constant::Binary(right)) => Constant::Binary(left + right),
-            (Constant::Octal(left), Constant::Binary(right)) => Constant::Binary(left + right),
-            (Constant::Integer(left), Constant::Hexadecimal(right)) => Constant::Integer(left + right),
-            (Constant::Binary(left), Constant::Hexadecimal(right)) => Constant::Binary(left + right),
-            (Constant::Hexadecimal(left), Constant::Hexadecimal(right)) => Constant::Hexadecimal(left + right),
-            (Constant::Octal(left), Constant::Hexadecimal(right)) => Constant::Hexadecimal(left + right),
-            (Constant::Integer(left), Constant::Octal(right)) => Constant::Integer(left + right),
-            (Constant::Binary(left), Constant::Octal(right)) => Constant::Binary(left + right),
-            (Constant::Hexadecimal(left), Constant::Octal(right)) => Constant::Hexadecimal(left + right),
-            (Constant::Octal(left), Constant::Octal(right)) => Constant::Octal(left + right),
-            _ => panic!("Invalid addition operation"),
-        }
+            (Constant::Integer(left), Constant::Integer(right)) => Constant::Integer(checked(left.checked_add(right))?),
+            (Constant::Binary(left), Constant::Integer(right)) => Constant::Integer(checked(left.checked_add(right))?),
+            (Constant::Hexadecimal(left), Constant::Integer(right)) => Constant::Integer(checked(left.checked_add(right))?),
+            (Constant::Octal(left), Constant::Integer(right)) => Constant::Integer(checked(left.checked_add(right))?),
+            (Constant::Integer(left), Constant::Binary(right)) => Constant::Integer(checked(left.checked_add(right))?),
+            (Constant::Binary(left), Constant::Binary(right)) => Constant::Binary(checked(left.checked_add(right))?),
+            (Constant::Hexadecimal(left), Constant::Binary(right)) => Constant::Binary(checked(left.checked_add(right))?),
+            (Constant::Octal(left), Constant::Binary(right)) => Constant::Binary(checked(left.checked_add(right))?),
+            (Constant::Integer(left), Constant::Hexadecimal(right)) => Constant::Integer(checked(left.checked_add(right))?),
+            (Constant::Binary(left), Constant::Hexadecimal(right)) => Constant::Binary(checked(left.checked_add(right))?),
+            (Constant::Hexadecimal(left), Constant::Hexadecimal(right)) => Constant::Hexadecimal(checked(left.checked_add(right))?),
+            (Constant::Octal(left), Constant::Hexadecimal(right)) => Constant::Hexadecimal(checked(left.checked_add(right))?),
+            (Constant::Integer(left), Constant::Octal(right)) => Constant::Integer(checked(left.checked_add(right))?),
+            (Constant::Binary(left), Constant::Octal(right)) => Constant::Binary(checked(left.checked_add(right))?),
+            (Constant::Hexadecimal(left), Constant::Octal(right)) => Constant::Hexadecimal(checked(left.checked_add(right))?),
+            (Constant::Octal(left), Constant::Octal(right)) => Constant::Octal(checked(left.checked_add(right))?),
+            _ => return Err("Invalid addition operation".to_string()),
+        })
     }
 }
 
@@ -131,18 +159,24 @@ impl Binary for AddExpression {
 /**
  * Multiplication expression (left * right) implementation
  */
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct MultiplyExpression {
     pub left: Box<EnumExpression>,
     pub right: Box<EnumExpression>,
 }
 
 impl Expression for MultiplyExpression {
-    fn evaluate(&self) -> Constant {
-        let left = self.left.evaluate();
-        let right = self.right.evaluate();
+    fn evaluate(&self, context: &Context) -> Result<Constant, String> {
+        let left = self.left.evaluate(context)?.numeric();
+        let right = self.right.evaluate(context)?.numeric();
+        if let Some(result) = matrix::combine(&left, &right, "*")? {
+            return Ok(result);
+        }
+        if let Some(result) = modular(context, &left, &right, |a, b, m| Ok(a.rem_euclid(m) * b.rem_euclid(m)))? {
+            return Ok(result);
+        }
         //match the left and right values, left value holds precedence
-        match (left, right) {
+        Ok(match (left, right) {
             (Constant::Integer(left), Constant::Float(right)) => Constant::Float(left as f64 * right),
             (Constant::Binary(left), Constant::Float(right)) => Constant::Float(left as f64 * right),
             (Constant::Hexadecimal(left), Constant::Float(right)) => Constant::Float(left as f64 * right),
@@ -152,24 +186,24 @@ impl Expression for MultiplyExpression {
             (Constant::Float(left), Constant::Hexadecimal(right)) => Constant::Float(left * right as f64),
             (Constant::Float(left), Constant::Octal(right)) => Constant::Float(left * right as f64),
             (Constant::Float(left), Constant::Float(right)) => Constant::Float(left * right),
-            (Constant::Integer(left), Constant::Integer(right)) => Constant::Integer(left * right),
-            (Constant::Binary(left), Constant::Integer(right)) => Constant::Integer(left * right),
-            (Constant::Hexadecimal(left), Constant::Integer(right)) => Constant::Integer(left * right),
-            (Constant::Octal(left), Constant::Integer(right)) => Constant::Integer(left * right),
-            (Constant::Integer(left), Constant::Binary(right)) => Constant::Integer(left * right),
-            (Constant::Binary(left), Constant::Binary(right)) => Constant::Binary(left * right),
-            (Constant::Hexadecimal(left), Constant::Binary(right)) => Constant::Binary(left * right),
-            (Constant::Octal(left), Constant::Binary(right)) => Constant::Binary(left * right),
-            (Constant::Integer(left), Constant::Hexadecimal(right)) => Constant::Integer(left * right),
-            (Constant::Binary(left), Constant::Hexadecimal(right)) => Constant::Binary(left * right),
-            (Constant::Hexadecimal(left), Constant::Hexadecimal(right)) => Constant::Hexadecimal(left * right),
-            (Constant::Octal(left), Constant::Hexadecimal(right)) => Constant::Hexadecimal(left * right),
-            (Constant::Integer(left), Constant::Octal(right)) => Constant::Integer(left * right),
-            (Constant::Binary(left), Constant::Octal(right)) => Constant::Binary(left * right),
-            (Constant::Hexadecimal(left), Constant::Octal(right)) => Constant::Hexadecimal(left * right),
-            (Constant::Octal(left), Constant::Octal(right)) => Constant::Octal(left * right),
-            _ => panic!("Invalid addition operation"),
-        }
+            (Constant::Integer(left), Constant::Integer(right)) => Constant::Integer(checked(left.checked_mul(right))?),
+            (Constant::Binary(left), Constant::Integer(right)) => Constant::Integer(checked(left.checked_mul(right))?),
+            (Constant::Hexadecimal(left), Constant::Integer(right)) => Constant::Integer(checked(left.checked_mul(right))?),
+            (Constant::Octal(left), Constant::Integer(right)) => Constant::Integer(checked(left.checked_mul(right))?),
+            (Constant::Integer(left), Constant::Binary(right)) => Constant::Integer(checked(left.checked_mul(right))?),
+            (Constant::Binary(left), Constant::Binary(right)) => Constant::Binary(checked(left.checked_mul(right))?),
+            (Constant::Hexadecimal(left), Constant::Binary(right)) => Constant::Binary(checked(left.checked_mul(right))?),
+            (Constant::Octal(left), Constant::Binary(right)) => Constant::Binary(checked(left.checked_mul(right))?),
+            (Constant::Integer(left), Constant::Hexadecimal(right)) => Constant::Integer(checked(left.checked_mul(right))?),
+            (Constant::Binary(left), Constant::Hexadecimal(right)) => Constant::Binary(checked(left.checked_mul(right))?),
+            (Constant::Hexadecimal(left), Constant::Hexadecimal(right)) => Constant::Hexadecimal(checked(left.checked_mul(right))?),
+            (Constant::Octal(left), Constant::Hexadecimal(right)) => Constant::Hexadecimal(checked(left.checked_mul(right))?),
+            (Constant::Integer(left), Constant::Octal(right)) => Constant::Integer(checked(left.checked_mul(right))?),
+            (Constant::Binary(left), Constant::Octal(right)) => Constant::Binary(checked(left.checked_mul(right))?),
+            (Constant::Hexadecimal(left), Constant::Octal(right)) => Constant::Hexadecimal(checked(left.checked_mul(right))?),
+            (Constant::Octal(left), Constant::Octal(right)) => Constant::Octal(checked(left.checked_mul(right))?),
+            _ => return Err("Invalid multiplication operation".to_string()),
+        })
     }
 }
 
@@ -181,18 +215,22 @@ impl Binary for MultiplyExpression {
 /**
  * Division expression (left / right) implementation
  */
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct DivideExpression {
     pub left: Box<EnumExpression>,
     pub right: Box<EnumExpression>,
 }
 
 impl Expression for DivideExpression {
-    fn evaluate(&self) -> Constant {
-        let left = self.left.evaluate();
-        let right = self.right.evaluate();
+    fn evaluate(&self, context: &Context) -> Result<Constant, String> {
+        let left = self.left.evaluate(context)?.numeric();
+        let right = self.right.evaluate(context)?.numeric();
+        if let Some(result) = modular(context, &left, &right, |a, b, m| Ok(a.rem_euclid(m) * inverse(b, m)?))? {
+            return Ok(result);
+        }
+        check_divisor(&left, &right)?;
         //match the left and right values, left value holds precedence
-        match (left, right) {
+        Ok(match (left, right) {
             (Constant::Integer(left), Constant::Float(right)) => Constant::Float(left as f64 / right),
             (Constant::Binary(left), Constant::Float(right)) => Constant::Float(left as f64 / right),
             (Constant::Hexadecimal(left), Constant::Float(right)) => Constant::Float(left as f64 / right),
@@ -218,8 +256,8 @@ impl Expression for DivideExpression {
             (Constant::Binary(left), Constant::Octal(right)) => Constant::Binary(left / right),
             (Constant::Hexadecimal(left), Constant::Octal(right)) => Constant::Hexadecimal(left / right),
             (Constant::Octal(left), Constant::Octal(right)) => Constant::Octal(left / right),
-            _ => panic!("Invalid addition operation"),
-        }
+            _ => return Err("Invalid division operation".to_string()),
+        })
     }
 }
 
@@ -231,44 +269,19 @@ impl Binary for DivideExpression {
 /**
  * Modulo expression (left % right) implementation
  */
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct ModuloExpression {
     pub left: Box<EnumExpression>,
     pub right: Box<EnumExpression>,
 }
 
 impl Expression for ModuloExpression {
-    fn evaluate(&self) -> Constant {
-        let left = self.left.evaluate();
-        let right = self.right.evaluate();
-        //match the left and right values, left value holds precedence
-        match (left, right) {
-            (Constant::Integer(left), Constant::Float(right)) => Constant::Float(left as f64 % right),
-            (Constant::Binary(left), Constant::Float(right)) => Constant::Float(left as f64 % right),
-            (Constant::Hexadecimal(left), Constant::Float(right)) => Constant::Float(left as f64 % right),
-            (Constant::Octal(left), Constant::Float(right)) => Constant::Float(left as f64 % right),
-            (Constant::Float(left), Constant::Integer(right)) => Constant::Float(left % right as f64),
-            (Constant::Float(left), Constant::Binary(right)) => Constant::Float(left % right as f64),
-            (Constant::Float(left), Constant::Hexadecimal(right)) => Constant::Float(left % right as f64),
-            (Constant::Float(left), Constant::Octal(right)) => Constant::Float(left % right as f64),
-            (Constant::Integer(left), Constant::Integer(right)) => Constant::Integer(left % right),
-            (Constant::Binary(left), Constant::Integer(right)) => Constant::Integer(left % right),
-            (Constant::Hexadecimal(left), Constant::Integer(right)) => Constant::Integer(left % right),
-            (Constant::Octal(left), Constant::Integer(right)) => Constant::Integer(left % right),
-            (Constant::Integer(left), Constant::Binary(right)) => Constant::Integer(left % right),
-            (Constant::Binary(left), Constant::Binary(right)) => Constant::Binary(left % right),
-            (Constant::Hexadecimal(left), Constant::Binary(right)) => Constant::Binary(left % right),
-            (Constant::Octal(left), Constant::Binary(right)) => Constant::Binary(left % right),
-            (Constant::Integer(left), Constant::Hexadecimal(right)) => Constant::Integer(left % right),
-            (Constant::Binary(left), Constant::Hexadecimal(right)) => Constant::Binary(left % right),
-            (Constant::Hexadecimal(left), Constant::Hexadecimal(right)) => Constant::Hexadecimal(left % right),
-            (Constant::Octal(left), Constant::Hexadecimal(right)) => Constant::Hexadecimal(left % right),
-            (Constant::Integer(left), Constant::Octal(right)) => Constant::Integer(left % right),
-            (Constant::Binary(left), Constant::Octal(right)) => Constant::Binary(left % right),
-            (Constant::Hexadecimal(left), Constant::Octal(right)) => Constant::Hexadecimal(left % right),
-            (Constant::Octal(left), Constant::Octal(right)) => Constant::Octal(left % right),
-            _ => panic!("Invalid addition operation"),
-        }
+    fn evaluate(&self, context: &Context) -> Result<Constant, String> {
+        let left = self.left.evaluate(context)?.numeric();
+        let right = self.right.evaluate(context)?.numeric();
+        check_modulus(context, "%")?;
+        check_divisor(&left, &right)?;
+        arithmetic(left, right, i64::checked_rem, |left, right| left % right)
     }
 }
 
@@ -278,21 +291,230 @@ impl Binary for ModuloExpression {
     }
 }
 
+/**
+ * Applies an integer or float operation to two constants
+    * A float on either side makes the result a float
+    * Otherwise Integer, Binary, Hexadecimal and Octal take precedence in that order
+ */
+fn arithmetic(left: Constant, right: Constant, integer: fn(i64, i64) -> Option<i64>, float: fn(f64, f64) -> f64) -> Result<Constant, String> {
+    let integer = |left: i64, right: i64| checked(integer(left, right));
+    Ok(match (left, right) {
+        (left, right) if !left.is_number() || !right.is_number() => return Err("Invalid arithmetic operation".to_string()),
+        (Constant::Float(left), right) => Constant::Float(float(left, right.into())),
+        (left, Constant::Float(right)) => Constant::Float(float(left.into(), right)),
+        (Constant::Integer(left), right) => Constant::Integer(integer(left, right.into())?),
+        (left, Constant::Integer(right)) => Constant::Integer(integer(left.into(), right)?),
+        (Constant::Binary(left), right) => Constant::Binary(integer(left, right.into())?),
+        (left, Constant::Binary(right)) => Constant::Binary(integer(left.into(), right)?),
+        (Constant::Hexadecimal(left), right) => Constant::Hexadecimal(integer(left, right.into())?),
+        (left, Constant::Hexadecimal(right)) => Constant::Hexadecimal(integer(left.into(), right)?),
+        (left, right) => Constant::Octal(integer(left.into(), right.into())?),
+    })
+}
+/**
+ * The result of a checked integer operation, an error when it does not fit in 64 bits
+ */
+fn checked(result: Option<i64>) -> Result<i64, String> {
+    result.ok_or_else(|| "The result overflows 64 bits".to_string())
+}
+/**
+ * Shifts by the right side, a negative shift or one of 64 bits or more has no result
+ */
+fn shift(left: i64, right: i64, shift: fn(i64, u32) -> Option<i64>) -> Result<i64, String> {
+    checked(u32::try_from(right).ok().and_then(|right| shift(left, right)))
+}
+/**
+ * Applies an operation to the integers of two constants when the context has a modulus
+    * The result is reduced into 0..modulus and keeps the kind picked by arithmetic
+    * Returns None outside of a mod N { ... } block so the caller does plain arithmetic instead
+ */
+fn modular(context: &Context, left: &Constant, right: &Constant, operation: fn(i128, i128, i128) -> Result<i128, String>) -> Result<Option<Constant>, String> {
+    let modulus = match context.modulus {
+        Some(modulus) => modulus,
+        None => return Ok(None),
+    };
+    if matches!(left, Constant::Float(_)) || matches!(right, Constant::Float(_)) {
+        return Err("Modular arithmetic only works on integers".to_string());
+    }
+    let kind = arithmetic(left.clone(), right.clone(), |_, _| Some(0), |_, _| 0.0)?;
+    let (a, b): (i64, i64) = (left.clone().into(), right.clone().into());
+    let result = operation(a as i128, b as i128, modulus as i128)?.rem_euclid(modulus as i128);
+    Ok(Some(kind.with_value(result as i64)))
+}
+/**
+ * Inverse of a modulo m as a wide integer, for division and negative exponents in modular arithmetic
+ */
+fn inverse(a: i128, m: i128) -> Result<i128, String> {
+    number::mod_inverse(a as i64, m as i64).map(|inverse| inverse as i128).ok_or(format!("{} has no inverse modulo {}", a, m))
+}
+/**
+ * Exponentiation modulo m, a negative exponent raises the inverse instead
+ */
+fn power(a: i128, b: i128, m: i128) -> Result<i128, String> {
+    let base = if b < 0 { inverse(a, m)? } else { a.rem_euclid(m) };
+    Ok(number::pow_mod(base as u64, b.unsigned_abs() as u64, m as u64) as i128)
+}
+/**
+ * Division with a remainder has no meaning modulo N, so %, rem, //, div and mod are errors inside a mod N { ... } block
+ */
+fn check_modulus(context: &Context, operator: &str) -> Result<(), String> {
+    match context.modulus {
+        Some(modulus) => Err(format!("{} does not work modulo {}, / multiplies by the inverse", operator, modulus)),
+        None => Ok(()),
+    }
+}
+/**
+ * Integer division by zero has no result, float division gives infinity or NaN instead
+    * The smallest integer divided by -1 is one more than the largest, so it has no result either
+ */
+fn check_divisor(left: &Constant, right: &Constant) -> Result<(), String> {
+    match (left, right) {
+        (Constant::Float(_), _) | (_, Constant::Float(_)) => Ok(()),
+        (_, Constant::Binary(0) | Constant::Integer(0) | Constant::Hexadecimal(0) | Constant::Octal(0)) => Err("Division by zero".to_string()),
+        (
+            Constant::Binary(i64::MIN) | Constant::Integer(i64::MIN) | Constant::Hexadecimal(i64::MIN) | Constant::Octal(i64::MIN),
+            Constant::Binary(-1) | Constant::Integer(-1) | Constant::Hexadecimal(-1) | Constant::Octal(-1),
+        ) => Err("The quotient overflows 64 bits".to_string()),
+        _ => Ok(()),
+    }
+}
+/**
+ * True division expression (left /. right) implementation
+    * Always divides as floats, so 7 /. 2 is 3.5
+ */
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct TrueDivideExpression {
+    pub left: Box<EnumExpression>,
+    pub right: Box<EnumExpression>,
+}
+
+impl Expression for TrueDivideExpression {
+    fn evaluate(&self, context: &Context) -> Result<Constant, String> {
+        let left = self.left.evaluate(context)?.numeric();
+        let right = self.right.evaluate(context)?.numeric();
+        if let Some(result) = modular(context, &left, &right, |a, b, m| Ok(a.rem_euclid(m) * inverse(b, m)?))? {
+            return Ok(result);
+        }
+        match (left, right) {
+            (left, right) if !left.is_number() || !right.is_number() => Err("Invalid division operation".to_string()),
+            (left, right) => Ok(Constant::Float(Into::<f64>::into(left) / Into::<f64>::into(right))),
+        }
+    }
+}
+
+impl Binary for TrueDivideExpression {
+    fn new(left: Box<EnumExpression>, right: Box<EnumExpression>) -> Self {
+        Self { left, right }
+    }
+}
+/**
+ * Floor division expression (left // right) implementation
+    * Rounds toward negative infinity, so -7 // 2 is -4
+ */
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct FloorDivideExpression {
+    pub left: Box<EnumExpression>,
+    pub right: Box<EnumExpression>,
+}
+
+impl Expression for FloorDivideExpression {
+    fn evaluate(&self, context: &Context) -> Result<Constant, String> {
+        let left = self.left.evaluate(context)?.numeric();
+        let right = self.right.evaluate(context)?.numeric();
+        check_modulus(context, "//")?;
+        check_divisor(&left, &right)?;
+        arithmetic(left, right, |left, right| {
+            let quotient = left.checked_div(right)?;
+            Some(if left % right != 0 && (left < 0) != (right < 0) { quotient - 1 } else { quotient })
+        }, |left, right| (left / right).floor())
+    }
+}
+
+impl Binary for FloorDivideExpression {
+    fn new(left: Box<EnumExpression>, right: Box<EnumExpression>) -> Self {
+        Self { left, right }
+    }
+}
+/**
+ * Euclidean division expression (left div right) implementation
+    * Pairs with mod so that left == right * (left div right) + left mod right
+ */
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct EuclideanDivideExpression {
+    pub left: Box<EnumExpression>,
+    pub right: Box<EnumExpression>,
+}
+
+impl Expression for EuclideanDivideExpression {
+    fn evaluate(&self, context: &Context) -> Result<Constant, String> {
+        let left = self.left.evaluate(context)?.numeric();
+        let right = self.right.evaluate(context)?.numeric();
+        check_modulus(context, "div")?;
+        check_divisor(&left, &right)?;
+        arithmetic(left, right, i64::checked_div_euclid, f64::div_euclid)
+    }
+}
+
+impl Binary for EuclideanDivideExpression {
+    fn new(left: Box<EnumExpression>, right: Box<EnumExpression>) -> Self {
+        Self { left, right }
+    }
+}
+/**
+ * Euclidean remainder expression (left mod right) implementation
+    * Never negative, so -7 mod 2 is 1
+ */
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct EuclideanModuloExpression {
+    pub left: Box<EnumExpression>,
+    pub right: Box<EnumExpression>,
+}
+
+impl Expression for EuclideanModuloExpression {
+    fn evaluate(&self, context: &Context) -> Result<Constant, String> {
+        let left = self.left.evaluate(context)?.numeric();
+        let right = self.right.evaluate(context)?.numeric();
+        check_modulus(context, "mod")?;
+        check_divisor(&left, &right)?;
+        arithmetic(left, right, i64::checked_rem_euclid, f64::rem_euclid)
+    }
+}
+
+impl Binary for EuclideanModuloExpression {
+    fn new(left: Box<EnumExpression>, right: Box<EnumExpression>) -> Self {
+        Self { left, right }
+    }
+}
+
 /**
  * Exponentiation expression (left ** right) implementation
  */
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct ExponentExpression {
     pub left: Box<EnumExpression>,
     pub right: Box<EnumExpression>,
 }
 
 impl Expression for ExponentExpression {
-    fn evaluate(&self) -> Constant {
-        let left = self.left.evaluate();
-        let right = self.right.evaluate();
+    fn evaluate(&self, context: &Context) -> Result<Constant, String> {
+        let left = self.left.evaluate(context)?.numeric();
+        let right = self.right.evaluate(&context.without_modulus())?.numeric();
+        if let Some(result) = modular(context, &left, &right, power)? {
+            return Ok(result);
+        }
+        //a negative exponent leaves the integers, so 2 ** -1 is 0.5
+        if let (
+            Constant::Integer(base) | Constant::Binary(base) | Constant::Hexadecimal(base) | Constant::Octal(base),
+            Constant::Integer(exponent) | Constant::Binary(exponent) | Constant::Hexadecimal(exponent) | Constant::Octal(exponent),
+        ) = (&left, &right) {
+            if *exponent < 0 {
+                return Ok(Constant::Float((*base as f64).powf(*exponent as f64)));
+            }
+        }
+        //an exponent past u32 overflows anything but 0, 1 and -1, so only whether it is even matters
+        let pow = |left: i64, right: i64| checked(left.checked_pow(u32::try_from(right).unwrap_or(u32::MAX - (right % 2 == 0) as u32)));
         //match the left and right values, left value holds precedence
-        match (left, right) {
+        Ok(match (left, right) {
             (Constant::Integer(left), Constant::Float(right)) => Constant::Float((left as f64).powf(right)),
             (Constant::Binary(left), Constant::Float(right)) => Constant::Float((left as f64).powf(right)),
             (Constant::Hexadecimal(left), Constant::Float(right)) => Constant::Float((left as f64).powf(right)),
@@ -301,24 +523,25 @@ impl Expression for ExponentExpression {
             (Constant::Float(left), Constant::Binary(right)) => Constant::Float(left.powf(right as f64)),
             (Constant::Float(left), Constant::Hexadecimal(right)) => Constant::Float(left.powf(right as f64)),
             (Constant::Float(left), Constant::Octal(right)) => Constant::Float(left.powf(right as f64)),
-            (Constant::Integer(left), Constant::Integer(right)) => Constant::Integer(left.pow(right as u32)),
-            (Constant::Binary(left), Constant::Integer(right)) => Constant::Integer(left.pow(right as u32)),
-            (Constant::Hexadecimal(left), Constant::Integer(right)) => Constant::Integer(left.pow(right as u32)),
-            (Constant::Octal(left), Constant::Integer(right)) => Constant::Integer(left.pow(right as u32)),
-            (Constant::Integer(left), Constant::Binary(right)) => Constant::Integer(left.pow(right as u32)),
-            (Constant::Binary(left), Constant::Binary(right)) => Constant::Binary(left.pow(right as u32)),
-            (Constant::Hexadecimal(left), Constant::Binary(right)) => Constant::Binary(left.pow(right as u32)),
-            (Constant::Octal(left), Constant::Binary(right)) => Constant::Binary(left.pow(right as u32)),
-            (Constant::Integer(left), Constant::Hexadecimal(right)) => Constant::Integer(left.pow(right as u32)),
-            (Constant::Binary(left), Constant::Hexadecimal(right)) => Constant::Binary(left.pow(right as u32)),
-            (Constant::Hexadecimal(left), Constant::Hexadecimal(right)) => Constant::Hexadecimal(left.pow(right as u32)),
-            (Constant::Octal(left), Constant::Hexadecimal(right)) => Constant::Hexadecimal(left.pow(right as u32)),
-            (Constant::Integer(left), Constant::Octal(right)) => Constant::Integer(left.pow(right as u32)),
-            (Constant::Binary(left), Constant::Octal(right)) => Constant::Binary(left.pow(right as u32)),
-            (Constant::Hexadecimal(left), Constant::Octal(right)) => Constant::Hexadecimal(left.pow(right as u32)),
-            (Constant::Octal(left), Constant::Octal(right)) => Constant::Octal(left.pow(right as u32)),
-            _ => panic!("Invalid addition operation"),
-        }
+            (Constant::Float(left), Constant::Float(right)) => Constant::Float(left.powf(right)),
+            (Constant::Integer(left), Constant::Integer(right)) => Constant::Integer(pow(left, right)?),
+            (Constant::Binary(left), Constant::Integer(right)) => Constant::Integer(pow(left, right)?),
+            (Constant::Hexadecimal(left), Constant::Integer(right)) => Constant::Integer(pow(left, right)?),
+            (Constant::Octal(left), Constant::Integer(right)) => Constant::Integer(pow(left, right)?),
+            (Constant::Integer(left), Constant::Binary(right)) => Constant::Integer(pow(left, right)?),
+            (Constant::Binary(left), Constant::Binary(right)) => Constant::Binary(pow(left, right)?),
+            (Constant::Hexadecimal(left), Constant::Binary(right)) => Constant::Binary(pow(left, right)?),
+            (Constant::Octal(left), Constant::Binary(right)) => Constant::Binary(pow(left, right)?),
+            (Constant::Integer(left), Constant::Hexadecimal(right)) => Constant::Integer(pow(left, right)?),
+            (Constant::Binary(left), Constant::Hexadecimal(right)) => Constant::Binary(pow(left, right)?),
+            (Constant::Hexadecimal(left), Constant::Hexadecimal(right)) => Constant::Hexadecimal(pow(left, right)?),
+            (Constant::Octal(left), Constant::Hexadecimal(right)) => Constant::Hexadecimal(pow(left, right)?),
+            (Constant::Integer(left), Constant::Octal(right)) => Constant::Integer(pow(left, right)?),
+            (Constant::Binary(left), Constant::Octal(right)) => Constant::Binary(pow(left, right)?),
+            (Constant::Hexadecimal(left), Constant::Octal(right)) => Constant::Hexadecimal(pow(left, right)?),
+            (Constant::Octal(left), Constant::Octal(right)) => Constant::Octal(pow(left, right)?),
+            _ => return Err("Invalid exponentiation operation".to_string()),
+        })
     }
 }
 
@@ -328,9 +551,10 @@ impl Binary for ExponentExpression {
     }
 }
 /**
- * Root expression (left // right) implementation
+ * Root expression (right √ left) implementation
+    * The left side is the radicand and the right side is the index
  */
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct RootExpression {
     pub left: Box<EnumExpression>,
     pub right: Box<EnumExpression>,
@@ -338,11 +562,11 @@ pub struct RootExpression {
 
 impl Expression for RootExpression {
     //the right must be an integer of some sort
-    fn evaluate(&self) -> Constant {
-        let left = self.left.evaluate();
-        let right = self.right.evaluate();
+    fn evaluate(&self, context: &Context) -> Result<Constant, String> {
+        let left = self.left.evaluate(context)?.numeric();
+        let right = self.right.evaluate(context)?.numeric();
         //match the left and right values, left value holds precedence
-        match (left, right) {
+        Ok(match (left, right) {
             (Constant::Integer(left), Constant::Integer(right)) => Constant::Integer((left as f64).powf(1.0 / right as f64) as i64),
             (Constant::Binary(left), Constant::Integer(right)) => Constant::Binary((left as f64).powf(1.0 / right as f64) as i64),
             (Constant::Hexadecimal(left), Constant::Integer(right)) => Constant::Hexadecimal((left as f64).powf(1.0 / right as f64) as i64),
@@ -363,8 +587,8 @@ impl Expression for RootExpression {
             (Constant::Hexadecimal(left), Constant::Octal(right)) => Constant::Hexadecimal((left as f64).powf(1.0 / right as f64) as i64),
             (Constant::Octal(left), Constant::Octal(right)) => Constant::Octal((left as f64).powf(1.0 / right as f64) as i64),
             (Constant::Float(left), Constant::Octal(right)) => Constant::Float(left.powf(1.0 / right as f64)),
-            _ => panic!("Invalid addition operation"),
-        }
+            _ => return Err("Invalid root operation".to_string()),
+        })
     }
 }
 
@@ -376,7 +600,7 @@ impl Binary for RootExpression {
 /**
  * Bitwise XOR expression (left ^ right) implementation
  */
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct XorExpression {
     pub left: Box<EnumExpression>,
     pub right: Box<EnumExpression>,
@@ -384,11 +608,11 @@ pub struct XorExpression {
 
 impl Expression for XorExpression {
     //the right must be an integer of some sort
-    fn evaluate(&self) -> Constant {
-        let left = self.left.evaluate();
-        let right = self.right.evaluate();
+    fn evaluate(&self, context: &Context) -> Result<Constant, String> {
+        let left = self.left.evaluate(context)?.numeric();
+        let right = self.right.evaluate(context)?.numeric();
         //match the left and right values, left value holds precedence
-        match (left, right) {
+        Ok(match (left, right) {
             (Constant::Integer(left), Constant::Integer(right)) => Constant::Integer(left ^ right),
             (Constant::Binary(left), Constant::Integer(right)) => Constant::Binary(left ^ right),
             (Constant::Hexadecimal(left), Constant::Integer(right)) => Constant::Hexadecimal(left ^ right),
@@ -410,8 +634,8 @@ impl Expression for XorExpression {
             (Constant::Octal(left), Constant::Octal(right)) => Constant::Octal(left ^ right),
             (Constant::Float(left), Constant::Octal(right)) => Constant::Integer(unsafe { *(&left as *const f64 as *const i64) } ^ right),
             (Constant::Float(left), Constant::Float(right)) => Constant::Integer(unsafe { *(&left as *const f64 as *const i64) } ^ unsafe { *(&right as *const f64 as *const i64) }),
-            _ => panic!("Invalid xor operation"),
-        }
+            _ => return Err("Invalid xor operation".to_string()),
+        })
     }
 }
 
@@ -423,7 +647,7 @@ impl Binary for XorExpression {
 /**
  * Bitwise AND expression (left & right) implementation
  */
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct AndExpression {
     pub left: Box<EnumExpression>,
     pub right: Box<EnumExpression>,
@@ -431,11 +655,11 @@ pub struct AndExpression {
 
 impl Expression for AndExpression {
     //the right must be an integer of some sort
-    fn evaluate(&self) -> Constant {
-        let left = self.left.evaluate();
-        let right = self.right.evaluate();
+    fn evaluate(&self, context: &Context) -> Result<Constant, String> {
+        let left = self.left.evaluate(context)?.numeric();
+        let right = self.right.evaluate(context)?.numeric();
         //match the left and right values, left value holds precedence
-        match (left, right) {
+        Ok(match (left, right) {
             (Constant::Integer(left), Constant::Integer(right)) => Constant::Integer(left & right),
             (Constant::Binary(left), Constant::Integer(right)) => Constant::Binary(left & right),
             (Constant::Hexadecimal(left), Constant::Integer(right)) => Constant::Hexadecimal(left & right),
@@ -457,8 +681,8 @@ impl Expression for AndExpression {
             (Constant::Octal(left), Constant::Octal(right)) => Constant::Octal(left & right),
             (Constant::Float(left), Constant::Octal(right)) => Constant::Integer(unsafe { *(&left as *const f64 as *const i64) } & right),
             (Constant::Float(left), Constant::Float(right)) => Constant::Integer(unsafe { *(&left as *const f64 as *const i64) } & unsafe { *(&right as *const f64 as *const i64) }),
-            _ => panic!("Invalid and operation"),
-        }
+            _ => return Err("Invalid and operation".to_string()),
+        })
     }
 }
 
@@ -470,18 +694,18 @@ impl Binary for AndExpression {
 /**
  * Bitwise OR expression (left | right) implementation
  */
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct OrExpression {
     pub left: Box<EnumExpression>,
     pub right: Box<EnumExpression>,
 }
 
 impl Expression for OrExpression {
-    fn evaluate(&self) -> Constant {
-        let left = self.left.evaluate();
-        let right = self.right.evaluate();
+    fn evaluate(&self, context: &Context) -> Result<Constant, String> {
+        let left = self.left.evaluate(context)?.numeric();
+        let right = self.right.evaluate(context)?.numeric();
         //match the left and right values, left value holds precedence
-        match (left, right) {
+        Ok(match (left, right) {
             (Constant::Integer(left), Constant::Integer(right)) => Constant::Integer(left | right),
             (Constant::Binary(left), Constant::Integer(right)) => Constant::Binary(left | right),
             (Constant::Hexadecimal(left), Constant::Integer(right)) => Constant::Hexadecimal(left | right),
@@ -503,8 +727,8 @@ impl Expression for OrExpression {
             (Constant::Octal(left), Constant::Octal(right)) => Constant::Octal(left | right),
             (Constant::Float(left), Constant::Octal(right)) => Constant::Integer(unsafe{*(&left as *const f64 as *const i64)} | right),
             (Constant::Float(left), Constant::Float(right)) => Constant::Integer(unsafe{*(&left as *const f64 as *const i64)} | unsafe{*(&right as *const f64 as *const i64)}),
-            _ => panic!("Invalid or operation"),
-        }
+            _ => return Err("Invalid or operation".to_string()),
+        })
     }
 }
 
@@ -516,40 +740,40 @@ impl Binary for OrExpression {
 /**
  * Bitwise Left Shift expression (left << right) implementation
  */
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct LeftShiftExpression {
     pub left: Box<EnumExpression>,
     pub right: Box<EnumExpression>,
 }
 
 impl Expression for LeftShiftExpression {
-    fn evaluate(&self) -> Constant {
-        let left = self.left.evaluate();
-        let right = self.right.evaluate();
+    fn evaluate(&self, context: &Context) -> Result<Constant, String> {
+        let left = self.left.evaluate(context)?.numeric();
+        let right = self.right.evaluate(context)?.numeric();
         //match the left and right values, left value holds precedence
-        match (left, right) {
-            (Constant::Integer(left), Constant::Integer(right)) => Constant::Integer(left << right),
-            (Constant::Binary(left), Constant::Integer(right)) => Constant::Binary(left << right),
-            (Constant::Hexadecimal(left), Constant::Integer(right)) => Constant::Hexadecimal(left << right),
-            (Constant::Octal(left), Constant::Integer(right)) => Constant::Octal(left << right),
-            (Constant::Float(left), Constant::Integer(right)) => Constant::Integer(unsafe { *(&left as *const f64 as *const i64) } << right),
-            (Constant::Integer(left), Constant::Binary(right)) => Constant::Binary(left << right),
-            (Constant::Binary(left), Constant::Binary(right)) => Constant::Binary(left << right),
-            (Constant::Hexadecimal(left), Constant::Binary(right)) => Constant::Binary(left << right),
-            (Constant::Octal(left), Constant::Binary(right)) => Constant::Binary(left << right),
-            (Constant::Float(left), Constant::Binary(right)) => Constant::Integer(unsafe { *(&left as *const f64 as *const i64) } << right),
-            (Constant::Integer(left), Constant::Hexadecimal(right)) => Constant::Hexadecimal(left << right),
-            (Constant::Binary(left), Constant::Hexadecimal(right)) => Constant::Binary(left << right),
-            (Constant::Hexadecimal(left), Constant::Hexadecimal(right)) => Constant::Hexadecimal(left << right),
-            (Constant::Octal(left), Constant::Hexadecimal(right)) => Constant::Hexadecimal(left << right),
-            (Constant::Float(left), Constant::Hexadecimal(right)) => Constant::Integer(unsafe { *(&left as *const f64 as *const i64) } << right),
-            (Constant::Integer(left), Constant::Octal(right)) => Constant::Octal(left << right),
-            (Constant::Binary(left), Constant::Octal(right)) => Constant::Binary(left << right),
-            (Constant::Hexadecimal(left), Constant::Octal(right)) => Constant::Hexadecimal(left << right),
-            (Constant::Octal(left), Constant::Octal(right)) => Constant::Octal(left << right),
-            (Constant::Float(left), Constant::Octal(right)) => Constant::Integer(unsafe { *(&left as *const f64 as *const i64) } << right),
-            _ => panic!("Invalid shift operation"),
-        }
+        Ok(match (left, right) {
+            (Constant::Integer(left), Constant::Integer(right)) => Constant::Integer(shift(left, right, i64::checked_shl)?),
+            (Constant::Binary(left), Constant::Integer(right)) => Constant::Binary(shift(left, right, i64::checked_shl)?),
+            (Constant::Hexadecimal(left), Constant::Integer(right)) => Constant::Hexadecimal(shift(left, right, i64::checked_shl)?),
+            (Constant::Octal(left), Constant::Integer(right)) => Constant::Octal(shift(left, right, i64::checked_shl)?),
+            (Constant::Float(left), Constant::Integer(right)) => Constant::Integer(shift(unsafe { *(&left as *const f64 as *const i64) }, right, i64::checked_shl)?),
+            (Constant::Integer(left), Constant::Binary(right)) => Constant::Binary(shift(left, right, i64::checked_shl)?),
+            (Constant::Binary(left), Constant::Binary(right)) => Constant::Binary(shift(left, right, i64::checked_shl)?),
+            (Constant::Hexadecimal(left), Constant::Binary(right)) => Constant::Binary(shift(left, right, i64::checked_shl)?),
+            (Constant::Octal(left), Constant::Binary(right)) => Constant::Binary(shift(left, right, i64::checked_shl)?),
+            (Constant::Float(left), Constant::Binary(right)) => Constant::Integer(shift(unsafe { *(&left as *const f64 as *const i64) }, right, i64::checked_shl)?),
+            (Constant::Integer(left), Constant::Hexadecimal(right)) => Constant::Hexadecimal(shift(left, right, i64::checked_shl)?),
+            (Constant::Binary(left), Constant::Hexadecimal(right)) => Constant::Binary(shift(left, right, i64::checked_shl)?),
+            (Constant::Hexadecimal(left), Constant::Hexadecimal(right)) => Constant::Hexadecimal(shift(left, right, i64::checked_shl)?),
+            (Constant::Octal(left), Constant::Hexadecimal(right)) => Constant::Hexadecimal(shift(left, right, i64::checked_shl)?),
+            (Constant::Float(left), Constant::Hexadecimal(right)) => Constant::Integer(shift(unsafe { *(&left as *const f64 as *const i64) }, right, i64::checked_shl)?),
+            (Constant::Integer(left), Constant::Octal(right)) => Constant::Octal(shift(left, right, i64::checked_shl)?),
+            (Constant::Binary(left), Constant::Octal(right)) => Constant::Binary(shift(left, right, i64::checked_shl)?),
+            (Constant::Hexadecimal(left), Constant::Octal(right)) => Constant::Hexadecimal(shift(left, right, i64::checked_shl)?),
+            (Constant::Octal(left), Constant::Octal(right)) => Constant::Octal(shift(left, right, i64::checked_shl)?),
+            (Constant::Float(left), Constant::Octal(right)) => Constant::Integer(shift(unsafe { *(&left as *const f64 as *const i64) }, right, i64::checked_shl)?),
+            _ => return Err("Invalid shift operation".to_string()),
+        })
     }
 }
 
@@ -561,40 +785,40 @@ impl Binary for LeftShiftExpression {
 /**
  * Bitwise Right Shift expression (left >> right) implementation
  */
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct RightShiftExpression {
     pub left: Box<EnumExpression>,
     pub right: Box<EnumExpression>,
 }
 
 impl Expression for RightShiftExpression {
-    fn evaluate(&self) -> Constant {
-        let left = self.left.evaluate();
-        let right = self.right.evaluate();
+    fn evaluate(&self, context: &Context) -> Result<Constant, String> {
+        let left = self.left.evaluate(context)?.numeric();
+        let right = self.right.evaluate(context)?.numeric();
         //match the left and right values, left value holds precedence
-        match (left, right) {
-            (Constant::Integer(left), Constant::Integer(right)) => Constant::Integer(left >> right),
-            (Constant::Binary(left), Constant::Integer(right)) => Constant::Binary(left >> right),
-            (Constant::Hexadecimal(left), Constant::Integer(right)) => Constant::Hexadecimal(left >> right),
-            (Constant::Octal(left), Constant::Integer(right)) => Constant::Octal(left >> right),
-            (Constant::Float(left), Constant::Integer(right)) => Constant::Integer(unsafe{*(&left as *const f64 as *const i64)} >> right),
-            (Constant::Integer(left), Constant::Binary(right)) => Constant::Binary(left >> right),
-            (Constant::Binary(left), Constant::Binary(right)) => Constant::Binary(left >> right),
-            (Constant::Hexadecimal(left), Constant::Binary(right)) => Constant::Binary(left >> right),
-            (Constant::Octal(left), Constant::Binary(right)) => Constant::Binary(left >> right),
-            (Constant::Float(left), Constant::Binary(right)) => Constant::Integer(unsafe{*(&left as *const f64 as *const i64)} >> right),
-            (Constant::Integer(left), Constant::Hexadecimal(right)) => Constant::Hexadecimal(left >> right),
-            (Constant::Binary(left), Constant::Hexadecimal(right)) => Constant::Binary(left >> right),
-            (Constant::Hexadecimal(left), Constant::Hexadecimal(right)) => Constant::Hexadecimal(left >> right),
-            (Constant::Octal(left), Constant::Hexadecimal(right)) => Constant::Hexadecimal(left >> right),
-            (Constant::Float(left), Constant::Hexadecimal(right)) => Constant::Integer(unsafe{*(&left as *const f64 as *const i64)} >> right),
-            (Constant::Integer(left), Constant::Octal(right)) => Constant::Octal(left >> right),
-            (Constant::Binary(left), Constant::Octal(right)) => Constant::Binary(left >> right),
-            (Constant::Hexadecimal(left), Constant::Octal(right)) => Constant::Hexadecimal(left >> right),
-            (Constant::Octal(left), Constant::Octal(right)) => Constant::Octal(left >> right),
-            (Constant::Float(left), Constant::Octal(right)) => Constant::Integer(unsafe{*(&left as *const f64 as *const i64)} >> right),
-            _ => panic!("Invalid shift operation"),
-        }
+        Ok(match (left, right) {
+            (Constant::Integer(left), Constant::Integer(right)) => Constant::Integer(shift(left, right, i64::checked_shr)?),
+            (Constant::Binary(left), Constant::Integer(right)) => Constant::Binary(shift(left, right, i64::checked_shr)?),
+            (Constant::Hexadecimal(left), Constant::Integer(right)) => Constant::Hexadecimal(shift(left, right, i64::checked_shr)?),
+            (Constant::Octal(left), Constant::Integer(right)) => Constant::Octal(shift(left, right, i64::checked_shr)?),
+            (Constant::Float(left), Constant::Integer(right)) => Constant::Integer(shift(unsafe{*(&left as *const f64 as *const i64)}, right, i64::checked_shr)?),
+            (Constant::Integer(left), Constant::Binary(right)) => Constant::Binary(shift(left, right, i64::checked_shr)?),
+            (Constant::Binary(left), Constant::Binary(right)) => Constant::Binary(shift(left, right, i64::checked_shr)?),
+            (Constant::Hexadecimal(left), Constant::Binary(right)) => Constant::Binary(shift(left, right, i64::checked_shr)?),
+            (Constant::Octal(left), Constant::Binary(right)) => Constant::Binary(shift(left, right, i64::checked_shr)?),
+            (Constant::Float(left), Constant::Binary(right)) => Constant::Integer(shift(unsafe{*(&left as *const f64 as *const i64)}, right, i64::checked_shr)?),
+            (Constant::Integer(left), Constant::Hexadecimal(right)) => Constant::Hexadecimal(shift(left, right, i64::checked_shr)?),
+            (Constant::Binary(left), Constant::Hexadecimal(right)) => Constant::Binary(shift(left, right, i64::checked_shr)?),
+            (Constant::Hexadecimal(left), Constant::Hexadecimal(right)) => Constant::Hexadecimal(shift(left, right, i64::checked_shr)?),
+            (Constant::Octal(left), Constant::Hexadecimal(right)) => Constant::Hexadecimal(shift(left, right, i64::checked_shr)?),
+            (Constant::Float(left), Constant::Hexadecimal(right)) => Constant::Integer(shift(unsafe{*(&left as *const f64 as *const i64)}, right, i64::checked_shr)?),
+            (Constant::Integer(left), Constant::Octal(right)) => Constant::Octal(shift(left, right, i64::checked_shr)?),
+            (Constant::Binary(left), Constant::Octal(right)) => Constant::Binary(shift(left, right, i64::checked_shr)?),
+            (Constant::Hexadecimal(left), Constant::Octal(right)) => Constant::Hexadecimal(shift(left, right, i64::checked_shr)?),
+            (Constant::Octal(left), Constant::Octal(right)) => Constant::Octal(shift(left, right, i64::checked_shr)?),
+            (Constant::Float(left), Constant::Octal(right)) => Constant::Integer(shift(unsafe{*(&left as *const f64 as *const i64)}, right, i64::checked_shr)?),
+            _ => return Err("Invalid shift operation".to_string()),
+        })
     }
 }
 
@@ -604,13 +828,241 @@ impl Binary for RightShiftExpression {
     }
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+/**
+ * Compares two constants, floats are compared by value and booleans as 1 and 0
+ */
+fn compare(left: Constant, right: Constant) -> Result<Option<Ordering>, String> {
+    Ok(match (left.numeric(), right.numeric()) {
+        (left, right) if !left.is_number() || !right.is_number() => return Err("Invalid comparison operation".to_string()),
+        (Constant::Float(left), right) => left.partial_cmp(&right.into()),
+        (left, Constant::Float(right)) => Into::<f64>::into(left).partial_cmp(&right),
+        (left, right) => Into::<i64>::into(left).partial_cmp(&right.into()),
+    })
+}
+/**
+ * Equality expression (left == right) implementation
+ */
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct EqualExpression {
+    pub left: Box<EnumExpression>,
+    pub right: Box<EnumExpression>,
+}
+
+impl Expression for EqualExpression {
+    fn evaluate(&self, context: &Context) -> Result<Constant, String> {
+        let left = self.left.evaluate(context)?;
+        let right = self.right.evaluate(context)?;
+        Ok(Constant::Boolean(compare(left, right)? == Some(Ordering::Equal)))
+    }
+}
+
+impl Binary for EqualExpression {
+    fn new(left: Box<EnumExpression>, right: Box<EnumExpression>) -> Self {
+        Self { left, right }
+    }
+}
+/**
+ * Inequality expression (left != right) implementation
+ */
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct NotEqualExpression {
+    pub left: Box<EnumExpression>,
+    pub right: Box<EnumExpression>,
+}
+
+impl Expression for NotEqualExpression {
+    fn evaluate(&self, context: &Context) -> Result<Constant, String> {
+        let left = self.left.evaluate(context)?;
+        let right = self.right.evaluate(context)?;
+        Ok(Constant::Boolean(compare(left, right)? != Some(Ordering::Equal)))
+    }
+}
+
+impl Binary for NotEqualExpression {
+    fn new(left: Box<EnumExpression>, right: Box<EnumExpression>) -> Self {
+        Self { left, right }
+    }
+}
+/**
+ * Less than expression (left < right) implementation
+ */
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct LessExpression {
+    pub left: Box<EnumExpression>,
+    pub right: Box<EnumExpression>,
+}
+
+impl Expression for LessExpression {
+    fn evaluate(&self, context: &Context) -> Result<Constant, String> {
+        let left = self.left.evaluate(context)?;
+        let right = self.right.evaluate(context)?;
+        Ok(Constant::Boolean(compare(left, right)? == Some(Ordering::Less)))
+    }
+}
+
+impl Binary for LessExpression {
+    fn new(left: Box<EnumExpression>, right: Box<EnumExpression>) -> Self {
+        Self { left, right }
+    }
+}
+/**
+ * Less than or equal expression (left <= right) implementation
+ */
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct LessEqualExpression {
+    pub left: Box<EnumExpression>,
+    pub right: Box<EnumExpression>,
+}
+
+impl Expression for LessEqualExpression {
+    fn evaluate(&self, context: &Context) -> Result<Constant, String> {
+        let left = self.left.evaluate(context)?;
+        let right = self.right.evaluate(context)?;
+        Ok(Constant::Boolean(matches!(compare(left, right)?, Some(Ordering::Less | Ordering::Equal))))
+    }
+}
+
+impl Binary for LessEqualExpression {
+    fn new(left: Box<EnumExpression>, right: Box<EnumExpression>) -> Self {
+        Self { left, right }
+    }
+}
+/**
+ * Greater than expression (left > right) implementation
+ */
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct GreaterExpression {
+    pub left: Box<EnumExpression>,
+    pub right: Box<EnumExpression>,
+}
+
+impl Expression for GreaterExpression {
+    fn evaluate(&self, context: &Context) -> Result<Constant, String> {
+        let left = self.left.evaluate(context)?;
+        let right = self.right.evaluate(context)?;
+        Ok(Constant::Boolean(compare(left, right)? == Some(Ordering::Greater)))
+    }
+}
+
+impl Binary for GreaterExpression {
+    fn new(left: Box<EnumExpression>, right: Box<EnumExpression>) -> Self {
+        Self { left, right }
+    }
+}
+/**
+ * Greater than or equal expression (left >= right) implementation
+ */
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct GreaterEqualExpression {
+    pub left: Box<EnumExpression>,
+    pub right: Box<EnumExpression>,
+}
+
+impl Expression for GreaterEqualExpression {
+    fn evaluate(&self, context: &Context) -> Result<Constant, String> {
+        let left = self.left.evaluate(context)?;
+        let right = self.right.evaluate(context)?;
+        Ok(Constant::Boolean(matches!(compare(left, right)?, Some(Ordering::Greater | Ordering::Equal))))
+    }
+}
+
+impl Binary for GreaterEqualExpression {
+    fn new(left: Box<EnumExpression>, right: Box<EnumExpression>) -> Self {
+        Self { left, right }
+    }
+}
+/**
+ * Logical AND expression (left && right) implementation
+    * The right side is only evaluated when the left side is true
+ */
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct LogicalAndExpression {
+    pub left: Box<EnumExpression>,
+    pub right: Box<EnumExpression>,
+}
+
+impl Expression for LogicalAndExpression {
+    fn evaluate(&self, context: &Context) -> Result<Constant, String> {
+        if !self.left.evaluate(context)?.is_truthy() {
+            return Ok(Constant::Boolean(false));
+        }
+        Ok(Constant::Boolean(self.right.evaluate(context)?.is_truthy()))
+    }
+}
+
+impl Binary for LogicalAndExpression {
+    fn new(left: Box<EnumExpression>, right: Box<EnumExpression>) -> Self {
+        Self { left, right }
+    }
+}
+/**
+ * Logical OR expression (left || right) implementation
+    * The right side is only evaluated when the left side is false
+ */
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct LogicalOrExpression {
+    pub left: Box<EnumExpression>,
+    pub right: Box<EnumExpression>,
+}
+
+impl Expression for LogicalOrExpression {
+    fn evaluate(&self, context: &Context) -> Result<Constant, String> {
+        if self.left.evaluate(context)?.is_truthy() {
+            return Ok(Constant::Boolean(true));
+        }
+        Ok(Constant::Boolean(self.right.evaluate(context)?.is_truthy()))
+    }
+}
+
+impl Binary for LogicalOrExpression {
+    fn new(left: Box<EnumExpression>, right: Box<EnumExpression>) -> Self {
+        Self { left, right }
+    }
+}
+
+/**
+ * Range expression (left..right) implementation
+    * Both ends are included and the range counts down when right is smaller than left
+ */
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct RangeExpression {
+    pub left: Box<EnumExpression>,
+    pub right: Box<EnumExpression>,
+}
+
+impl Expression for RangeExpression {
+    fn evaluate(&self, context: &Context) -> Result<Constant, String> {
+        let left = self.left.evaluate(context)?.numeric();
+        let right = self.right.evaluate(context)?.numeric();
+        if !left.is_number() || !right.is_number() || matches!(left, Constant::Float(_)) || matches!(right, Constant::Float(_)) {
+            return Err("Ranges only work on integers".to_string());
+        }
+        let (start, end): (i64, i64) = (left.clone().into(), right.into());
+        if start.abs_diff(end) >= RANGE_LIMIT {
+            return Err(format!("Ranges can not be longer than {} numbers", RANGE_LIMIT));
+        }
+        let items: Vec<i64> = if start <= end { (start..=end).collect() } else { (end..=start).rev().collect() };
+        Ok(Constant::List(items.into_iter().map(|i| left.with_value(i)).collect()))
+    }
+}
+
+impl Binary for RangeExpression {
+    fn new(left: Box<EnumExpression>, right: Box<EnumExpression>) -> Self {
+        Self { left, right }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub enum BinaryExpression {
     Addition(AddExpression),
     Subtraction(SubtractExpression),
     Multiplication(MultiplyExpression),
     Division(DivideExpression),
     Modulus(ModuloExpression),
+    TrueDivision(TrueDivideExpression),
+    FloorDivision(FloorDivideExpression),
+    EuclideanDivision(EuclideanDivideExpression),
+    EuclideanModulus(EuclideanModuloExpression),
     Exponent(ExponentExpression),
     Root(RootExpression),
     BitwiseAnd(AndExpression),
@@ -618,23 +1070,109 @@ pub enum BinaryExpression {
     BitwiseXor(XorExpression),
     LeftShift(LeftShiftExpression),
     RightShift(RightShiftExpression),
+    Equal(EqualExpression),
+    NotEqual(NotEqualExpression),
+    Less(LessExpression),
+    LessEqual(LessEqualExpression),
+    Greater(GreaterExpression),
+    GreaterEqual(GreaterEqualExpression),
+    LogicalAnd(LogicalAndExpression),
+    LogicalOr(LogicalOrExpression),
+    Range(RangeExpression),
 }
 
 impl BinaryExpression {
-    pub fn evaluate(&self) -> Constant {
+    pub fn evaluate(&self, context: &Context) -> Result<Constant, String> {
         match self {
-            BinaryExpression::Addition(expression) => expression.evaluate(),
-            BinaryExpression::Subtraction(expression) => expression.evaluate(),
-            BinaryExpression::Multiplication(expression) => expression.evaluate(),
-            BinaryExpression::Division(expression) => expression.evaluate(),
-            BinaryExpression::Modulus(expression) => expression.evaluate(),
-            BinaryExpression::Exponent(expression) => expression.evaluate(),
-            BinaryExpression::Root(expression) => expression.evaluate(),
-            BinaryExpression::BitwiseAnd(expression) => expression.evaluate(),
-            BinaryExpression::BitwiseOr(expression) => expression.evaluate(),
-            BinaryExpression::BitwiseXor(expression) => expression.evaluate(),
-            BinaryExpression::LeftShift(expression) => expression.evaluate(),
-            BinaryExpression::RightShift(expression) => expression.evaluate(),
+            BinaryExpression::Addition(expression) => expression.evaluate(context),
+            BinaryExpression::Subtraction(expression) => expression.evaluate(context),
+            BinaryExpression::Multiplication(expression) => expression.evaluate(context),
+            BinaryExpression::Division(expression) => expression.evaluate(context),
+            BinaryExpression::Modulus(expression) => expression.evaluate(context),
+            BinaryExpression::TrueDivision(expression) => expression.evaluate(context),
+            BinaryExpression::FloorDivision(expression) => expression.evaluate(context),
+            BinaryExpression::EuclideanDivision(expression) => expression.evaluate(context),
+            BinaryExpression::EuclideanModulus(expression) => expression.evaluate(context),
+            BinaryExpression::Exponent(expression) => expression.evaluate(context),
+            BinaryExpression::Root(expression) => expression.evaluate(context),
+            BinaryExpression::BitwiseAnd(expression) => expression.evaluate(context),
+            BinaryExpression::BitwiseOr(expression) => expression.evaluate(context),
+            BinaryExpression::BitwiseXor(expression) => expression.evaluate(context),
+            BinaryExpression::LeftShift(expression) => expression.evaluate(context),
+            BinaryExpression::RightShift(expression) => expression.evaluate(context),
+            BinaryExpression::Equal(expression) => expression.evaluate(context),
+            BinaryExpression::NotEqual(expression) => expression.evaluate(context),
+            BinaryExpression::Less(expression) => expression.evaluate(context),
+            BinaryExpression::LessEqual(expression) => expression.evaluate(context),
+            BinaryExpression::Greater(expression) => expression.evaluate(context),
+            BinaryExpression::GreaterEqual(expression) => expression.evaluate(context),
+            BinaryExpression::LogicalAnd(expression) => expression.evaluate(context),
+            BinaryExpression::LogicalOr(expression) => expression.evaluate(context),
+            BinaryExpression::Range(expression) => expression.evaluate(context),
+        }
+    }
+    /**
+     The operator as it is written, the inverse of new
+     */
+    pub fn operator(&self) -> &'static str {
+        match self {
+            BinaryExpression::Addition(_) => "+",
+            BinaryExpression::Subtraction(_) => "-",
+            BinaryExpression::Multiplication(_) => "*",
+            BinaryExpression::Division(_) => "/",
+            BinaryExpression::Modulus(_) => "%",
+            BinaryExpression::TrueDivision(_) => "/.",
+            BinaryExpression::FloorDivision(_) => "//",
+            BinaryExpression::EuclideanDivision(_) => "div",
+            BinaryExpression::EuclideanModulus(_) => "mod",
+            BinaryExpression::Exponent(_) => "**",
+            BinaryExpression::Root(_) => "√",
+            BinaryExpression::BitwiseAnd(_) => "&",
+            BinaryExpression::BitwiseOr(_) => "|",
+            BinaryExpression::BitwiseXor(_) => "^",
+            BinaryExpression::LeftShift(_) => "<<",
+            BinaryExpression::RightShift(_) => ">>",
+            BinaryExpression::Equal(_) => "==",
+            BinaryExpression::NotEqual(_) => "!=",
+            BinaryExpression::Less(_) => "<",
+            BinaryExpression::LessEqual(_) => "<=",
+            BinaryExpression::Greater(_) => ">",
+            BinaryExpression::GreaterEqual(_) => ">=",
+            BinaryExpression::LogicalAnd(_) => "&&",
+            BinaryExpression::LogicalOr(_) => "||",
+            BinaryExpression::Range(_) => "..",
+        }
+    }
+    /**
+     The left and right side, a root keeps its radicand on the left and its index on the right
+     */
+    pub fn operands(&self) -> (&EnumExpression, &EnumExpression) {
+        match self {
+            BinaryExpression::Addition(expression) => (&expression.left, &expression.right),
+            BinaryExpression::Subtraction(expression) => (&expression.left, &expression.right),
+            BinaryExpression::Multiplication(expression) => (&expression.left, &expression.right),
+            BinaryExpression::Division(expression) => (&expression.left, &expression.right),
+            BinaryExpression::Modulus(expression) => (&expression.left, &expression.right),
+            BinaryExpression::TrueDivision(expression) => (&expression.left, &expression.right),
+            BinaryExpression::FloorDivision(expression) => (&expression.left, &expression.right),
+            BinaryExpression::EuclideanDivision(expression) => (&expression.left, &expression.right),
+            BinaryExpression::EuclideanModulus(expression) => (&expression.left, &expression.right),
+            BinaryExpression::Exponent(expression) => (&expression.left, &expression.right),
+            BinaryExpression::Root(expression) => (&expression.left, &expression.right),
+            BinaryExpression::BitwiseAnd(expression) => (&expression.left, &expression.right),
+            BinaryExpression::BitwiseOr(expression) => (&expression.left, &expression.right),
+            BinaryExpression::BitwiseXor(expression) => (&expression.left, &expression.right),
+            BinaryExpression::LeftShift(expression) => (&expression.left, &expression.right),
+            BinaryExpression::RightShift(expression) => (&expression.left, &expression.right),
+            BinaryExpression::Equal(expression) => (&expression.left, &expression.right),
+            BinaryExpression::NotEqual(expression) => (&expression.left, &expression.right),
+            BinaryExpression::Less(expression) => (&expression.left, &expression.right),
+            BinaryExpression::LessEqual(expression) => (&expression.left, &expression.right),
+            BinaryExpression::Greater(expression) => (&expression.left, &expression.right),
+            BinaryExpression::GreaterEqual(expression) => (&expression.left, &expression.right),
+            BinaryExpression::LogicalAnd(expression) => (&expression.left, &expression.right),
+            BinaryExpression::LogicalOr(expression) => (&expression.left, &expression.right),
+            BinaryExpression::Range(expression) => (&expression.left, &expression.right),
         }
     }
     pub fn new(
@@ -647,35 +1185,48 @@ impl BinaryExpression {
             "-" => BinaryExpression::Subtraction(SubtractExpression::new(left, right)),
             "*" => BinaryExpression::Multiplication(MultiplyExpression::new(left, right)),
             "/" => BinaryExpression::Division(DivideExpression::new(left, right)),
-            "%" => BinaryExpression::Modulus(ModuloExpression::new(left, right)),
+            "%" | "rem" => BinaryExpression::Modulus(ModuloExpression::new(left, right)),
+            "/." => BinaryExpression::TrueDivision(TrueDivideExpression::new(left, right)),
+            "//" => BinaryExpression::FloorDivision(FloorDivideExpression::new(left, right)),
+            "div" => BinaryExpression::EuclideanDivision(EuclideanDivideExpression::new(left, right)),
+            "mod" => BinaryExpression::EuclideanModulus(EuclideanModuloExpression::new(left, right)),
             "&" => BinaryExpression::BitwiseAnd(AndExpression::new(left, right)),
             "|" => BinaryExpression::BitwiseOr(OrExpression::new(left, right)),
             "^" => BinaryExpression::BitwiseXor(XorExpression::new(left, right)),
             "**" => BinaryExpression::Exponent(ExponentExpression::new(left, right)),
-            "//" => BinaryExpression::Root(RootExpression::new(left, right)),
+            "√" => BinaryExpression::Root(RootExpression::new(left, right)),
             "<<" => BinaryExpression::LeftShift(LeftShiftExpression::new(left, right)),
             ">>" => BinaryExpression::RightShift(RightShiftExpression::new(left, right)),
+            "==" => BinaryExpression::Equal(EqualExpression::new(left, right)),
+            "!=" => BinaryExpression::NotEqual(NotEqualExpression::new(left, right)),
+            "<" => BinaryExpression::Less(LessExpression::new(left, right)),
+            "<=" => BinaryExpression::LessEqual(LessEqualExpression::new(left, right)),
+            ">" => BinaryExpression::Greater(GreaterExpression::new(left, right)),
+            ">=" => BinaryExpression::GreaterEqual(GreaterEqualExpression::new(left, right)),
+            "&&" => BinaryExpression::LogicalAnd(LogicalAndExpression::new(left, right)),
+            "||" => BinaryExpression::LogicalOr(LogicalOrExpression::new(left, right)),
+            ".." => BinaryExpression::Range(RangeExpression::new(left, right)),
             _ => panic!("Invalid binary operator"),
         }
     }
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct NotExpression {
     pub expression: Box<EnumExpression>,
 }
 
 impl Expression for NotExpression {
-    fn evaluate(&self) -> Constant {
-        let expression = self.expression.evaluate();
-        match expression {
+    fn evaluate(&self, context: &Context) -> Result<Constant, String> {
+        let expression = self.expression.evaluate(context)?.numeric();
+        Ok(match expression {
             Constant::Integer(value) => Constant::Integer(!value),
             Constant::Binary(value) => Constant::Binary(!value),
             Constant::Hexadecimal(value) => Constant::Hexadecimal(!value),
             Constant::Octal(value) => Constant::Octal(!value),
             Constant::Float(value) => Constant::Integer(!(unsafe{*(&value as *const f64 as *const i64)})),
-            _ => panic!("Invalid addition operation"),
-        }
+            _ => return Err("Invalid not operation".to_string()),
+        })
     }
 }
 
@@ -685,22 +1236,25 @@ impl Unary for NotExpression {
     }
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct NegateExpression {
     pub expression: Box<EnumExpression>,
 }
 
 impl Expression for NegateExpression {
-    fn evaluate(&self) -> Constant {
-        let expression = self.expression.evaluate();
-        match expression {
-            Constant::Integer(value) => Constant::Integer(-value),
-            Constant::Binary(value) => Constant::Binary(-value),
-            Constant::Hexadecimal(value) => Constant::Hexadecimal(-value),
-            Constant::Octal(value) => Constant::Octal(-value),
-            Constant::Float(value) => Constant::Float(-value),
-            _ => panic!("Invalid addition operation"),
+    fn evaluate(&self, context: &Context) -> Result<Constant, String> {
+        let expression = self.expression.evaluate(context)?.numeric();
+        if let Some(result) = modular(context, &Constant::Integer(0), &expression, |_, b, _| Ok(-b))? {
+            return Ok(expression.with_value(result.into()));
         }
+        Ok(match expression {
+            Constant::Integer(value) => Constant::Integer(checked(value.checked_neg())?),
+            Constant::Binary(value) => Constant::Binary(checked(value.checked_neg())?),
+            Constant::Hexadecimal(value) => Constant::Hexadecimal(checked(value.checked_neg())?),
+            Constant::Octal(value) => Constant::Octal(checked(value.checked_neg())?),
+            Constant::Float(value) => Constant::Float(-value),
+            _ => return Err("Invalid negation operation".to_string()),
+        })
     }
 }
 
@@ -710,45 +1264,322 @@ impl Unary for NegateExpression {
     }
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+/**
+ * Percentage expression (value%) implementation
+    * On its own it is the value divided by 100, on the right of + and - it is a share of the left side
+ */
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct PercentExpression {
+    pub expression: Box<EnumExpression>,
+}
+
+impl Expression for PercentExpression {
+    fn evaluate(&self, context: &Context) -> Result<Constant, String> {
+        Ok(Constant::Float(self.rate(context)?))
+    }
+}
+
+impl Unary for PercentExpression {
+    fn new(expression: Box<EnumExpression>) -> Self {
+        Self { expression }
+    }
+}
+
+impl PercentExpression {
+    fn rate(&self, context: &Context) -> Result<f64, String> {
+        let expression = self.expression.evaluate(context)?.numeric();
+        if !expression.is_number() {
+            return Err("Invalid percentage".to_string());
+        }
+        let value: f64 = expression.into();
+        Ok(value / 100.0)
+    }
+    /**
+     Grows or shrinks the base by the percentage, sign is 1 for an increase and -1 for a decrease
+     */
+    fn change(&self, base: Constant, sign: f64, context: &Context) -> Result<Constant, String> {
+        if !base.is_number() {
+            return Err("Invalid percentage".to_string());
+        }
+        let base: f64 = base.into();
+        Ok(Constant::Float(base * (1.0 + sign * self.rate(context)?)))
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub enum UnaryExpression {
     Not(NotExpression),
     Negate(NegateExpression),
+    Percent(PercentExpression),
 }
 
 impl UnaryExpression {
-    pub fn evaluate(&self) -> Constant {
+    pub fn evaluate(&self, context: &Context) -> Result<Constant, String> {
+        match self {
+            UnaryExpression::Not(expression) => expression.evaluate(context),
+            UnaryExpression::Negate(expression) => expression.evaluate(context),
+            UnaryExpression::Percent(expression) => expression.evaluate(context),
+        }
+    }
+    pub fn operator(&self) -> &'static str {
+        match self {
+            UnaryExpression::Not(_) => "!",
+            UnaryExpression::Negate(_) => "-",
+            UnaryExpression::Percent(_) => "%",
+        }
+    }
+    pub fn operand(&self) -> &EnumExpression {
         match self {
-            UnaryExpression::Not(expression) => expression.evaluate(),
-            UnaryExpression::Negate(expression) => expression.evaluate(),
+            UnaryExpression::Not(expression) => &expression.expression,
+            UnaryExpression::Negate(expression) => &expression.expression,
+            UnaryExpression::Percent(expression) => &expression.expression,
         }
     }
     pub fn new(expression: Box<EnumExpression>, operator: &str) -> UnaryExpression {
         match operator {
             "!" => UnaryExpression::Not(NotExpression::new(expression)),
             "-" => UnaryExpression::Negate(NegateExpression::new(expression)),
+            "%" => UnaryExpression::Percent(PercentExpression::new(expression)),
             _ => panic!("Invalid unary operator"),
         }
     }
 }
 
+/**
+ * Conditional expression (condition ? consequent : alternative) implementation
+    * Only the branch picked by the condition is evaluated
+ */
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct ConditionalExpression {
+    pub condition: Box<EnumExpression>,
+    pub consequent: Box<EnumExpression>,
+    pub alternative: Box<EnumExpression>,
+}
+
+impl Expression for ConditionalExpression {
+    fn evaluate(&self, context: &Context) -> Result<Constant, String> {
+        if self.condition.evaluate(context)?.is_truthy() {
+            self.consequent.evaluate(context)
+        } else {
+            self.alternative.evaluate(context)
+        }
+    }
+}
+
+impl ConditionalExpression {
+    pub fn new(condition: Box<EnumExpression>, consequent: Box<EnumExpression>, alternative: Box<EnumExpression>) -> Self {
+        Self { condition, consequent, alternative }
+    }
+}
+
+/**
+ * Function call expression (name(arguments, ...)) implementation
+ */
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct CallExpression {
+    pub name: String,
+    pub arguments: Vec<EnumExpression>,
+}
+
+impl Expression for CallExpression {
+    fn evaluate(&self, context: &Context) -> Result<Constant, String> {
+        //a variable holding a function shadows the built in function of the same name
+        if let Some(Constant::Function(lambda)) = context.variables.get(&self.name) {
+            let arguments = self.arguments.iter().map(|argument| argument.evaluate(context)).collect::<Result<Vec<Constant>, String>>()?;
+            return lambda.call(arguments, context);
+        }
+        functions::call(&self.name, &self.arguments, context)
+    }
+}
+
+impl CallExpression {
+    pub fn new(name: String, arguments: Vec<EnumExpression>) -> Self {
+        Self { name, arguments }
+    }
+}
+
+/**
+ * Variable expression (name) implementation
+ */
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct VariableExpression {
+    pub name: String,
+}
+
+impl Expression for VariableExpression {
+    fn evaluate(&self, context: &Context) -> Result<Constant, String> {
+        if let Some(value) = context.variables.get(&self.name) {
+            return Ok(value.clone());
+        }
+        //variables of the same name shadow the constants
+        match self.name.as_str() {
+            "pi" => Ok(Constant::Float(std::f64::consts::PI)),
+            "tau" => Ok(Constant::Float(std::f64::consts::TAU)),
+            "e" => Ok(Constant::Float(std::f64::consts::E)),
+            "true" => Ok(Constant::Boolean(true)),
+            "false" => Ok(Constant::Boolean(false)),
+            //one of each angle unit in the unit of the context, so 30deg is 30 degrees whatever the angle mode is
+            "deg" => Ok(Constant::Float(AngleUnit::Degrees.radians() / context.angle.radians())),
+            "rad" => Ok(Constant::Float(AngleUnit::Radians.radians() / context.angle.radians())),
+            "grad" => Ok(Constant::Float(AngleUnit::Gradians.radians() / context.angle.radians())),
+            _ => Err(format!("Unknown variable: {}", self.name)),
+        }
+    }
+}
+
+impl VariableExpression {
+    pub fn new(name: String) -> Self {
+        Self { name }
+    }
+}
+
+/**
+ * Lambda expression (x -> body or (a, x) -> body) implementation
+    * Evaluates to a function that captures the variables around it
+ */
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct LambdaExpression {
+    pub parameters: Vec<String>,
+    pub body: Box<EnumExpression>,
+}
+
+impl Expression for LambdaExpression {
+    fn evaluate(&self, context: &Context) -> Result<Constant, String> {
+        Ok(Constant::Function(Lambda {
+            parameters: self.parameters.clone(),
+            body: Arc::new((*self.body).clone()),
+            captured: context.variables.clone(),
+        }))
+    }
+}
+
+impl LambdaExpression {
+    pub fn new(parameters: Vec<String>, body: Box<EnumExpression>) -> Self {
+        Self { parameters, body }
+    }
+}
+
+/**
+ * A function value, the body runs with the captured variables and the arguments bound to the parameters
+ */
+#[derive(Debug, Clone)]
+pub struct Lambda {
+    pub parameters: Vec<String>,
+    pub body: Arc<EnumExpression>,
+    pub captured: HashMap<String, Constant>,
+}
+
+impl PartialEq for Lambda {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.body, &other.body) && self.captured == other.captured
+    }
+}
+
+impl Lambda {
+    pub fn call(&self, arguments: Vec<Constant>, context: &Context) -> Result<Constant, String> {
+        if arguments.len() != self.parameters.len() {
+            return Err(format!("The function takes {} arguments but got {}", self.parameters.len(), arguments.len()));
+        }
+        //captured variables shadow the ones of the caller, which lets a named function call itself
+        let mut inner = context.clone();
+        inner.variables.extend(self.captured.clone());
+        inner.variables.extend(self.parameters.iter().cloned().zip(arguments));
+        self.body.evaluate(&inner)
+    }
+}
+
+/**
+ * List expression ([item, ...]) implementation
+    * [[1, 2], [3, 4]] makes a matrix instead
+ */
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct ListExpression {
+    pub items: Vec<EnumExpression>,
+}
+
+impl Expression for ListExpression {
+    fn evaluate(&self, context: &Context) -> Result<Constant, String> {
+        let items = self.items.iter().map(|item| item.evaluate(context)).collect::<Result<Vec<Constant>, String>>()?;
+        //a list of equally long lists of numbers is written down as a matrix
+        if items.iter().all(|item| matches!(item, Constant::List(_))) {
+            if let Some(rows) = matrix::from_constant(&Constant::List(items.clone())) {
+                return Ok(Constant::Matrix(rows));
+            }
+        }
+        Ok(Constant::List(items))
+    }
+}
+
+impl ListExpression {
+    pub fn new(items: Vec<EnumExpression>) -> Self {
+        Self { items }
+    }
+}
+
+/**
+ * Modular block expression (mod modulus { expression }) implementation
+    * Every +, -, *, / and ** inside the block is reduced modulo the modulus
+ */
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct ModularExpression {
+    pub modulus: Box<EnumExpression>,
+    pub expression: Box<EnumExpression>,
+}
+
+impl Expression for ModularExpression {
+    fn evaluate(&self, context: &Context) -> Result<Constant, String> {
+        let modulus = match self.modulus.evaluate(context)?.numeric() {
+            modulus if !modulus.is_number() || matches!(modulus, Constant::Float(_)) => return Err("The modulus has to be an integer".to_string()),
+            modulus => modulus.into(),
+        };
+        if modulus <= 0 {
+            return Err("The modulus has to be positive".to_string());
+        }
+        Ok(match self.expression.evaluate(&context.with_modulus(modulus))? {
+            value @ (Constant::Integer(_) | Constant::Binary(_) | Constant::Hexadecimal(_) | Constant::Octal(_)) => {
+                let i: i64 = value.clone().into();
+                value.with_value(i.rem_euclid(modulus))
+            },
+            value => value,
+        })
+    }
+}
+
+impl ModularExpression {
+    pub fn new(modulus: Box<EnumExpression>, expression: Box<EnumExpression>) -> Self {
+        Self { modulus, expression }
+    }
+}
+
 /**
  * EnumExpression is the enumerated version of the Expression trait.
     * It is used serialize and deserialize the Expression trait.
  */
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub enum EnumExpression {
     Constant(ConstExpression),
     Binary(BinaryExpression),
     Unary(UnaryExpression),
+    Conditional(ConditionalExpression),
+    Call(CallExpression),
+    Modular(ModularExpression),
+    List(ListExpression),
+    Variable(VariableExpression),
+    Lambda(LambdaExpression),
 }
 
 impl Expression for EnumExpression {
-    fn evaluate(&self) -> Constant {
+    fn evaluate(&self, context: &Context) -> Result<Constant, String> {
         match self {
-            EnumExpression::Constant(expression) => expression.evaluate(),
-            EnumExpression::Binary(expression) => expression.evaluate(),
-            EnumExpression::Unary(expression) => expression.evaluate(),
+            EnumExpression::Constant(expression) => expression.evaluate(context),
+            EnumExpression::Binary(expression) => expression.evaluate(context),
+            EnumExpression::Unary(expression) => expression.evaluate(context),
+            EnumExpression::Conditional(expression) => expression.evaluate(context),
+            EnumExpression::Call(expression) => expression.evaluate(context),
+            EnumExpression::Modular(expression) => expression.evaluate(context),
+            EnumExpression::List(expression) => expression.evaluate(context),
+            EnumExpression::Variable(expression) => expression.evaluate(context),
+            EnumExpression::Lambda(expression) => expression.evaluate(context),
         }
     }
 }
\ No newline at end of file
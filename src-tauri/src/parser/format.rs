@@ -0,0 +1,276 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{constants::Constant, number};
+
+/**
+ How floats are written out.
+ * Scientific always has one digit before the point and engineering keeps the exponent a multiple of 3
+ */
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum Notation {
+    #[default]
+    Plain,
+    Scientific,
+    Engineering,
+}
+
+/**
+ How a result is shown, handed over next to the Target.
+ * The defaults show every constant exactly as its plain serialization does
+ */
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct FormatOptions {
+    /**
+     Fixed number of decimal places for floats, wins over significant
+     */
+    pub decimals: Option<usize>,
+    /**
+     Number of significant digits for floats
+     */
+    pub significant: Option<usize>,
+    pub notation: Notation,
+    /**
+     Floats as the closest fraction, 0.75 as 3/4, wins over decimals and notation
+     */
+    pub fraction: bool,
+    /**
+     Largest denominator a fraction may have, 1000 when not set
+     */
+    pub denominator: Option<i64>,
+    /**
+     A fraction this close to the float is good enough, the closest one under the denominator bound is used when not set
+     */
+    pub tolerance: Option<f64>,
+    /**
+     Thousands separators for decimals, groups of 4 digits for binary and hexadecimal and of 3 for octal
+     */
+    pub grouping: bool,
+    /**
+     Upper case hexadecimal digits
+     */
+    pub uppercase: bool,
+    /**
+     Shows integers as a word of this many bits, binary, hexadecimal and octal zero padded and in two's complement
+     */
+    pub width: Option<u32>,
+    /**
+     Whether a decimal integer is read as a signed word, the top bit of the word is the sign
+     */
+    pub signed: bool,
+    /**
+     Whether binary, hexadecimal and octal start with 0b, 0x and 0o
+     */
+    pub prefix: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            decimals: None,
+            significant: None,
+            notation: Notation::Plain,
+            fraction: false,
+            denominator: None,
+            tolerance: None,
+            grouping: false,
+            uppercase: false,
+            width: None,
+            signed: true,
+            prefix: true,
+        }
+    }
+}
+
+/**
+ A constant together with the options it is shown with, serializes to the same shape as the constant.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Formatted {
+    pub constant: Constant,
+    pub options: FormatOptions,
+}
+
+impl Serialize for Formatted {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut map = HashMap::<&str, String>::new();
+        let (key, s) = self.constant.describe(&self.options);
+        map.insert(key, s);
+        map.serialize(serializer)
+    }
+}
+
+/**
+ One number in every base at once, the panel of a programmer calculator.
+ * Integer views follow the targets, so a float shows its bits there too
+ */
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Representations {
+    /**
+     Decimal, with a width the top bit of the word is the sign
+     */
+    pub signed: String,
+    pub unsigned: String,
+    pub hexadecimal: String,
+    pub octal: String,
+    pub binary: String,
+    pub float: String,
+    /**
+     The bits of the value as a 64 bit IEEE 754 float
+     */
+    pub ieee: String,
+}
+
+impl Representations {
+    pub fn new(constant: Constant, options: &FormatOptions) -> Result<Self, String> {
+        let constant = constant.numeric();
+        if !constant.is_number() {
+            return Err("Only a single number can be shown in every base".to_string());
+        }
+        let f: f64 = constant.clone().into();
+        let i: i64 = constant.into();
+        let ieee = FormatOptions { width: Some(64), ..options.clone() };
+        Ok(Self {
+            signed: integer(i, &FormatOptions { signed: true, ..options.clone() }),
+            unsigned: integer(i, &FormatOptions { signed: false, ..options.clone() }),
+            hexadecimal: radix(i, 16, options),
+            octal: radix(i, 8, options),
+            binary: radix(i, 2, options),
+            float: float(f, options),
+            ieee: radix(f.to_bits() as i64, 16, &ieee),
+        })
+    }
+}
+
+/**
+ Writes an integer in decimal.
+ * With a width only the lowest bits count, read as a signed or an unsigned word
+ */
+pub fn integer(i: i64, options: &FormatOptions) -> String {
+    let width = options.width.unwrap_or(64).clamp(1, 64);
+    let unsigned = if width == 64 { i as u64 } else { (i as u64) & ((1u64 << width) - 1) };
+    let value = if options.signed && unsigned >> (width - 1) == 1 { unsigned as i128 - (1i128 << width) } else { unsigned as i128 };
+    let s = value.unsigned_abs().to_string();
+    let s = if options.grouping { group(&s, 3, ',') } else { s };
+    if value < 0 { format!("-{}", s) } else { s }
+}
+
+/**
+ Writes a float with the decimals, significant digits and notation of the options.
+ */
+pub fn float(f: f64, options: &FormatOptions) -> String {
+    if !f.is_finite() {
+        return format!("{:.}", f);
+    }
+    if options.fraction {
+        if let Some((numerator, denominator)) = number::rational(f, options.denominator.unwrap_or(1000), options.tolerance.unwrap_or(0.0)) {
+            let numerator = integer(numerator, options);
+            return if denominator == 1 { numerator } else { format!("{}/{}", numerator, denominator) };
+        }
+    }
+    match options.notation {
+        Notation::Plain => {
+            let s = match (options.decimals, options.significant) {
+                (Some(decimals), _) => format!("{:.*}", decimals, f),
+                (None, Some(significant)) => format!("{:.}", round_significant(f, significant)),
+                (None, None) => format!("{:.}", f),
+            };
+            if options.grouping { group_decimal(&s) } else { s }
+        },
+        Notation::Scientific | Notation::Engineering => {
+            let step = if options.notation == Notation::Engineering { 3 } else { 1 };
+            let exponent = if f == 0.0 { 0 } else { f.abs().log10().floor() as i32 };
+            let mut exponent = exponent - exponent.rem_euclid(step);
+            //rounding can carry the mantissa over to the next power, 9.999 to 10.00
+            if round(f / 10f64.powi(exponent), options).abs() >= 10f64.powi(step) {
+                exponent += step;
+            }
+            let mantissa = round(f / 10f64.powi(exponent), options);
+            match options.decimals {
+                Some(decimals) => format!("{:.*}e{}", decimals, mantissa, exponent),
+                None => format!("{:.}e{}", mantissa, exponent),
+            }
+        },
+    }
+}
+
+/**
+ Rounds a mantissa to the decimals or significant digits of the options, 15 significant digits hide float noise otherwise.
+ */
+fn round(mantissa: f64, options: &FormatOptions) -> f64 {
+    match (options.decimals, options.significant) {
+        (Some(decimals), _) => format!("{:.*}", decimals, mantissa).parse().unwrap_or(mantissa),
+        (None, Some(significant)) => round_significant(mantissa, significant),
+        (None, None) => round_significant(mantissa, 15),
+    }
+}
+
+/**
+ Writes an integer in base 2, 8 or 16.
+ * Negative numbers are shown in two's complement, as 64 bits unless a width is set
+ */
+pub fn radix(i: i64, radix: u32, options: &FormatOptions) -> String {
+    let width = options.width.unwrap_or(64).clamp(1, 64);
+    let value = if width == 64 { i as u64 } else { (i as u64) & ((1u64 << width) - 1) };
+    let mut s = match radix {
+        2 => format!("{:b}", value),
+        8 => format!("{:o}", value),
+        _ if options.uppercase => format!("{:X}", value),
+        _ => format!("{:x}", value),
+    };
+    if options.width.is_some() {
+        let bits = match radix { 2 => 1, 8 => 3, _ => 4 };
+        let digits = ((width + bits - 1) / bits) as usize;
+        s = format!("{:0>1$}", s, digits);
+    }
+    if options.grouping {
+        s = group(&s, if radix == 8 { 3 } else { 4 }, '_');
+    }
+    if !options.prefix {
+        return s;
+    }
+    let prefix = match radix { 2 => "0b", 8 => "0o", _ => "0x" };
+    format!("{}{}", prefix, s)
+}
+
+fn round_significant(f: f64, significant: usize) -> f64 {
+    if f == 0.0 {
+        return 0.0;
+    }
+    //formatting in scientific notation rounds to the digits, reading it back drops the exponent form
+    format!("{:.*e}", significant.max(1) - 1, f).parse().unwrap_or(f)
+}
+
+/**
+ Puts the separator between every size digits, counting from the right.
+ */
+fn group(digits: &str, size: usize, separator: char) -> String {
+    let mut s = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % size == 0 {
+            s.push(separator);
+        }
+        s.push(c);
+    }
+    s
+}
+
+/**
+ Groups the whole part of a written decimal number into thousands.
+ */
+fn group_decimal(s: &str) -> String {
+    let (sign, s) = match s.strip_prefix('-') {
+        Some(s) => ("-", s),
+        None => ("", s),
+    };
+    let (whole, fraction) = match s.split_once('.') {
+        Some((whole, fraction)) => (whole, format!(".{}", fraction)),
+        None => (s, String::new()),
+    };
+    format!("{}{}{}", sign, group(whole, 3, ','), fraction)
+}
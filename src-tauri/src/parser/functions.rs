@@ -0,0 +1,514 @@
+use super::{constants::Constant, context::{AngleUnit, Context}, expressions::{EnumExpression, Expression, Lambda}, gf2, matrix, number, numeric, symbolic};
+
+/**
+ Calls a built in function by name.
+ * The arguments are handed over unevaluated so a function can decide how to use them
+ */
+pub fn call(name: &str, arguments: &[EnumExpression], context: &Context) -> Result<Constant, String> {
+    //symbolic and numeric functions work on the expressions themselves, their variables do not need a value
+    match name {
+        "diff" => return symbolic::diff(arguments, context),
+        "simplify" => return symbolic::simplify(arguments, context),
+        "solve" => return numeric::solve(arguments, context),
+        "root" => return numeric::root(arguments, context),
+        "integrate" => return numeric::integrate(arguments, context),
+        "integrate_err" => return numeric::integrate_err(arguments, context),
+        "sum" | "product" | "prod" if numeric::is_series(arguments) => return total(name, &numeric::terms(name, arguments, context)?),
+        _ => {},
+    }
+    let values = arguments.iter().map(|argument| argument.evaluate(context).map(Constant::numeric)).collect::<Result<Vec<Constant>, String>>()?;
+    match name {
+        "gcd" => {
+            at_least(name, &values, 1)?;
+            let mut result = 0i128;
+            for value in &values {
+                result = number::gcd(result, integer(name, value)? as i128);
+            }
+            Ok(values[0].with_value(fits(name, result)?))
+        },
+        "lcm" => {
+            at_least(name, &values, 1)?;
+            let mut result = 1i128;
+            for value in &values {
+                let value = integer(name, value)? as i128;
+                if value == 0 || result == 0 {
+                    result = 0;
+                    continue;
+                }
+                result = (result / number::gcd(result, value) * value).abs();
+                fits(name, result)?;
+            }
+            Ok(values[0].with_value(fits(name, result)?))
+        },
+        "factorial" => {
+            arity(name, &values, 1)?;
+            let n = integer(name, &values[0])?;
+            if n < 0 {
+                return Err("factorial is not defined for negative numbers".to_string());
+            }
+            let mut result = 1i64;
+            for i in 2..=n {
+                result = result.checked_mul(i).ok_or(format!("factorial({}) does not fit in 64 bits", n))?;
+            }
+            Ok(values[0].with_value(result))
+        },
+        "nCr" => {
+            arity(name, &values, 2)?;
+            let (n, r) = (integer(name, &values[0])?, integer(name, &values[1])?);
+            if n < 0 || r < 0 {
+                return Err("nCr is not defined for negative numbers".to_string());
+            }
+            if r > n {
+                return Ok(values[0].with_value(0));
+            }
+            //C(n, i) grows until i reaches n / 2, so every step stays below the result
+            let r = r.min(n - r) as i128;
+            let mut result = 1i128;
+            for i in 0..r {
+                result = result * (n as i128 - i) / (i + 1);
+                fits(name, result)?;
+            }
+            Ok(values[0].with_value(fits(name, result)?))
+        },
+        "nPr" => {
+            arity(name, &values, 2)?;
+            let (n, r) = (integer(name, &values[0])?, integer(name, &values[1])?);
+            if n < 0 || r < 0 {
+                return Err("nPr is not defined for negative numbers".to_string());
+            }
+            if r > n {
+                return Ok(values[0].with_value(0));
+            }
+            let mut result = 1i64;
+            for i in (n - r + 1)..=n {
+                result = result.checked_mul(i).ok_or(format!("nPr({}, {}) does not fit in 64 bits", n, r))?;
+            }
+            Ok(values[0].with_value(result))
+        },
+        "modpow" => {
+            arity(name, &values, 3)?;
+            let (base, exponent, modulus) = (integer(name, &values[0])?, integer(name, &values[1])?, modulus(name, &values[2])?);
+            //a negative exponent raises the inverse instead
+            let base = if exponent < 0 {
+                number::mod_inverse(base, modulus).ok_or(format!("{} has no inverse modulo {}", base, modulus))?
+            } else {
+                base.rem_euclid(modulus)
+            };
+            let result = number::pow_mod(base as u64, exponent.unsigned_abs(), modulus as u64);
+            Ok(values[0].with_value(result as i64))
+        },
+        "modinv" => {
+            arity(name, &values, 2)?;
+            let (a, modulus) = (integer(name, &values[0])?, modulus(name, &values[1])?);
+            let result = number::mod_inverse(a, modulus).ok_or(format!("{} has no inverse modulo {}", a, modulus))?;
+            Ok(values[0].with_value(result))
+        },
+        "isprime" => {
+            arity(name, &values, 1)?;
+            let n = integer(name, &values[0])?;
+            Ok(Constant::Boolean(n > 1 && number::is_prime(n as u64)))
+        },
+        "nextprime" => {
+            arity(name, &values, 1)?;
+            let n = integer(name, &values[0])?;
+            let result = number::next_prime(n).ok_or(format!("There is no 64 bit prime after {}", n))?;
+            Ok(values[0].with_value(result))
+        },
+        "factor" => {
+            arity(name, &values, 1)?;
+            let n = integer(name, &values[0])?;
+            if n == 0 {
+                return Err("0 has no prime factorization".to_string());
+            }
+            let mut factors = Vec::new();
+            if n < 0 {
+                factors.push((-1, 1));
+            }
+            for (prime, exponent) in number::factorize(n.unsigned_abs()) {
+                factors.push((prime as i64, exponent));
+            }
+            Ok(Constant::Factors(factors))
+        },
+        "clmul" => {
+            arity(name, &values, 2)?;
+            let (a, b) = (integer(name, &values[0])?, integer(name, &values[1])?);
+            let result = gf2::clmul(a as u64, b as u64);
+            let result = u64::try_from(result).map_err(|_| format!("The result of {} does not fit in 64 bits", name))?;
+            Ok(values[0].with_value(result as i64))
+        },
+        "polydiv" | "polymod" => {
+            arity(name, &values, 2)?;
+            let (a, b) = (integer(name, &values[0])?, integer(name, &values[1])?);
+            if b == 0 {
+                return Err("Division by zero".to_string());
+            }
+            let (quotient, remainder) = gf2::divmod(a as u64, b as u64);
+            Ok(values[0].with_value(if name == "polydiv" { quotient } else { remainder } as i64))
+        },
+        "reflect" => {
+            if values.len() != 1 {
+                arity(name, &values, 2)?;
+            }
+            let a = integer(name, &values[0])?;
+            //without a width every bit of the 64 bit value is reversed
+            let width = match values.get(1) {
+                Some(width) => integer(name, width)?,
+                None => 64,
+            };
+            if !(1..=64).contains(&width) {
+                return Err(format!("{} needs a width between 1 and 64", name));
+            }
+            Ok(values[0].with_value(gf2::reflect(a as u64, width as u32) as i64))
+        },
+        "sin" | "cos" | "tan" => {
+            arity(name, &values, 1)?;
+            let x = float(name, &values[0])? * context.angle.radians();
+            let value = match name {
+                "sin" => x.sin(),
+                "cos" => x.cos(),
+                _ => x.tan(),
+            };
+            Ok(Constant::Float(value))
+        },
+        "asin" | "acos" | "atan" => {
+            arity(name, &values, 1)?;
+            let x = float(name, &values[0])?;
+            let value = match name {
+                "asin" => x.asin(),
+                "acos" => x.acos(),
+                _ => x.atan(),
+            };
+            Ok(Constant::Float(defined(name, x, value)? / context.angle.radians()))
+        },
+        "atan2" => {
+            arity(name, &values, 2)?;
+            let (y, x) = (float(name, &values[0])?, float(name, &values[1])?);
+            Ok(Constant::Float(y.atan2(x) / context.angle.radians()))
+        },
+        "sinh" | "cosh" | "tanh" | "exp" | "ln" | "sqrt" => {
+            arity(name, &values, 1)?;
+            let x = float(name, &values[0])?;
+            let value = match name {
+                "sinh" => x.sinh(),
+                "cosh" => x.cosh(),
+                "tanh" => x.tanh(),
+                "exp" => x.exp(),
+                "ln" => x.ln(),
+                _ => x.sqrt(),
+            };
+            Ok(Constant::Float(defined(name, x, value)?))
+        },
+        //an angle in a unit, the same as the suffix so deg(30) is 30deg
+        "deg" | "rad" | "grad" => {
+            arity(name, &values, 1)?;
+            Ok(Constant::Float(float(name, &values[0])? * angle(name).radians() / context.angle.radians()))
+        },
+        //an angle in the unit of the context converted to another unit, todeg(pi) is 180 in radians
+        "todeg" | "torad" | "tograd" => {
+            arity(name, &values, 1)?;
+            Ok(Constant::Float(float(name, &values[0])? * context.angle.radians() / angle(&name[2..]).radians()))
+        },
+        "cf" => {
+            //the expansion stops after 20 terms unless told otherwise
+            let (x, terms) = match values.as_slice() {
+                [x] => (x, 20),
+                [x, terms] => (x, integer(name, terms)?),
+                _ => return Err(format!("{} takes 1 or 2 arguments but got {}", name, values.len())),
+            };
+            if !x.is_number() || !(1..=1000).contains(&terms) {
+                return Err(format!("{} needs a number and between 1 and 1000 terms", name));
+            }
+            let x: f64 = x.clone().into();
+            Ok(Constant::List(number::continued_fraction(x, terms as usize).into_iter().map(Constant::Integer).collect()))
+        },
+        "sum" | "product" | "prod" => total(name, &items(name, &values)?),
+        "mean" => {
+            let floats = floats(&items(name, &values)?);
+            Ok(Constant::Float(mean(&floats)))
+        },
+        "variance" | "stddev" => {
+            let floats = floats(&items(name, &values)?);
+            if floats.len() < 2 {
+                return Err(format!("{} needs at least 2 numbers", name));
+            }
+            //sample variance, divided by n - 1
+            let mean = mean(&floats);
+            let variance = floats.iter().map(|f| (f - mean).powi(2)).sum::<f64>() / (floats.len() - 1) as f64;
+            Ok(Constant::Float(if name == "variance" { variance } else { variance.sqrt() }))
+        },
+        "median" => {
+            let items = sorted(items(name, &values)?);
+            let middle = items.len() / 2;
+            if items.len() % 2 == 1 {
+                return Ok(items[middle].clone());
+            }
+            Ok(Constant::Float(mean(&floats(&items[middle - 1..=middle]))))
+        },
+        "mode" => {
+            //the most common number, the smallest one wins a tie
+            let items = sorted(items(name, &values)?);
+            let floats = floats(&items);
+            let (mut best, mut best_count, mut count) = (0, 0, 0);
+            for i in 0..items.len() {
+                count = if i > 0 && floats[i] == floats[i - 1] { count + 1 } else { 1 };
+                if count > best_count {
+                    (best, best_count) = (i, count);
+                }
+            }
+            Ok(items[best].clone())
+        },
+        "min" | "max" => {
+            let items = sorted(items(name, &values)?);
+            Ok(if name == "min" { items[0].clone() } else { items[items.len() - 1].clone() })
+        },
+        "percentile" => {
+            arity(name, &values, 2)?;
+            let items = floats(&sorted(items(name, &values[..1])?));
+            let p: f64 = match &values[1] {
+                p if !p.is_number() => return Err(format!("{} needs a number between 0 and 100", name)),
+                p => p.clone().into(),
+            };
+            if !(0.0..=100.0).contains(&p) {
+                return Err(format!("{} needs a number between 0 and 100", name));
+            }
+            //linear interpolation between the two closest ranks
+            let rank = p / 100.0 * (items.len() - 1) as f64;
+            let (low, high) = (rank.floor() as usize, rank.ceil() as usize);
+            Ok(Constant::Float(items[low] + (items[high] - items[low]) * (rank - low as f64)))
+        },
+        "map" => {
+            arity(name, &values, 2)?;
+            let (items, lambda) = (list(name, &values[0])?, function(name, &values[1])?);
+            let items = items.iter().map(|item| lambda.call(vec![item.clone()], context)).collect::<Result<Vec<Constant>, String>>()?;
+            Ok(Constant::List(items))
+        },
+        "filter" => {
+            arity(name, &values, 2)?;
+            let (items, lambda) = (list(name, &values[0])?, function(name, &values[1])?);
+            let mut kept = Vec::new();
+            for item in items {
+                if lambda.call(vec![item.clone()], context)?.is_truthy() {
+                    kept.push(item);
+                }
+            }
+            Ok(Constant::List(kept))
+        },
+        "reduce" => {
+            //without a starting value the first item is used
+            let (items, mut accumulator, lambda) = match values.as_slice() {
+                [items, lambda] => {
+                    let items = list(name, items)?;
+                    if items.is_empty() {
+                        return Err(format!("{} of an empty list needs a starting value", name));
+                    }
+                    (items[1..].to_vec(), items[0].clone(), function(name, lambda)?)
+                },
+                [items, start, lambda] => (list(name, items)?, start.clone(), function(name, lambda)?),
+                _ => return Err(format!("{} takes 2 or 3 arguments but got {}", name, values.len())),
+            };
+            for item in items {
+                accumulator = lambda.call(vec![accumulator, item], context)?;
+            }
+            Ok(accumulator)
+        },
+        "zip" => {
+            at_least(name, &values, 1)?;
+            let lists = values.iter().map(|value| list(name, value)).collect::<Result<Vec<Vec<Constant>>, String>>()?;
+            let length = lists.iter().map(|list| list.len()).min().unwrap_or(0);
+            Ok(Constant::List((0..length).map(|i| Constant::List(lists.iter().map(|list| list[i].clone()).collect())).collect()))
+        },
+        "transpose" => {
+            arity(name, &values, 1)?;
+            Ok(Constant::Matrix(matrix::transpose(&matrix(name, &values[0])?)))
+        },
+        "det" => {
+            arity(name, &values, 1)?;
+            matrix::determinant(&matrix(name, &values[0])?)
+        },
+        "inv" => {
+            arity(name, &values, 1)?;
+            Ok(Constant::Matrix(matrix::inverse(&matrix(name, &values[0])?)?))
+        },
+        "rank" => {
+            arity(name, &values, 1)?;
+            Ok(Constant::Integer(matrix::rank(&matrix(name, &values[0])?) as i64))
+        },
+        "linsolve" => {
+            arity(name, &values, 2)?;
+            let x = matrix::solve(&matrix(name, &values[0])?, &matrix(name, &values[1])?)?;
+            //a vector on the right gives a vector back
+            Ok(if let Constant::List(_) = values[1] { matrix::to_vector(x) } else { Constant::Matrix(x) })
+        },
+        "identity" => {
+            arity(name, &values, 1)?;
+            let n = integer(name, &values[0])?;
+            if !(1..=1000).contains(&n) {
+                return Err(format!("{} needs a size between 1 and 1000", name));
+            }
+            Ok(Constant::Matrix(matrix::identity(n as usize)))
+        },
+        _ => Err(format!("Unknown function: {}", name)),
+    }
+}
+
+/**
+ Checks that a function got exactly the number of arguments it takes.
+ */
+fn arity(name: &str, values: &[Constant], count: usize) -> Result<(), String> {
+    if values.len() != count {
+        return Err(format!("{} takes {} arguments but got {}", name, count, values.len()));
+    }
+    Ok(())
+}
+
+/**
+ Checks that a variadic function got enough arguments.
+ */
+fn at_least(name: &str, values: &[Constant], count: usize) -> Result<(), String> {
+    if values.len() < count {
+        return Err(format!("{} takes at least {} arguments but got {}", name, count, values.len()));
+    }
+    Ok(())
+}
+
+/**
+ Reads an integer argument, floats are only accepted when they hold a whole number.
+ */
+fn integer(name: &str, value: &Constant) -> Result<i64, String> {
+    match value {
+        Constant::Float(f) if f.fract() == 0.0 && f.abs() < i64::MAX as f64 => Ok(*f as i64),
+        value if !value.is_number() || matches!(value, Constant::Float(_)) => Err(format!("{} only works on integers", name)),
+        value => Ok(value.clone().into()),
+    }
+}
+
+/**
+ The unit of an angle suffix, deg, rad or grad.
+ */
+fn angle(suffix: &str) -> AngleUnit {
+    match suffix {
+        "deg" => AngleUnit::Degrees,
+        "rad" => AngleUnit::Radians,
+        _ => AngleUnit::Gradians,
+    }
+}
+
+/**
+ Reads any number argument as a float.
+ */
+fn float(name: &str, value: &Constant) -> Result<f64, String> {
+    if !value.is_number() {
+        return Err(format!("{} only works on numbers", name));
+    }
+    Ok(value.clone().into())
+}
+
+/**
+ Turns a NaN that a number outside of the domain gives into an error, asin(2) or ln(-1).
+ */
+fn defined(name: &str, x: f64, value: f64) -> Result<f64, String> {
+    if value.is_nan() && !x.is_nan() {
+        return Err(format!("{} is not defined for {}", name, x));
+    }
+    Ok(value)
+}
+
+/**
+ Reads a list argument, a matrix is a list of its rows.
+ */
+fn list(name: &str, value: &Constant) -> Result<Vec<Constant>, String> {
+    match value {
+        Constant::List(items) => Ok(items.clone()),
+        Constant::Matrix(rows) => Ok(rows.iter().map(|row| Constant::List(row.iter().copied().map(matrix::entry).collect())).collect()),
+        _ => Err(format!("{} needs a list", name)),
+    }
+}
+
+/**
+ Reads a matrix argument, lists of numbers count as column vectors.
+ */
+fn matrix(name: &str, value: &Constant) -> Result<matrix::Matrix, String> {
+    matrix::from_constant(value).ok_or(format!("{} needs a matrix", name))
+}
+
+/**
+ Reads a function argument, like x -> x * 2.
+ */
+fn function<'a>(name: &str, value: &'a Constant) -> Result<&'a Lambda, String> {
+    match value {
+        Constant::Function(lambda) => Ok(lambda),
+        _ => Err(format!("{} needs a function", name)),
+    }
+}
+
+/**
+ Reads a modulus argument, which has to be positive.
+ */
+fn modulus(name: &str, value: &Constant) -> Result<i64, String> {
+    let modulus = integer(name, value)?;
+    if modulus <= 0 {
+        return Err(format!("{} needs a positive modulus", name));
+    }
+    Ok(modulus)
+}
+
+/**
+ Narrows a wide intermediate result back to 64 bits.
+ */
+fn fits(name: &str, value: i128) -> Result<i64, String> {
+    i64::try_from(value).map_err(|_| format!("The result of {} does not fit in 64 bits", name))
+}
+
+/**
+ The numbers a statistics function works on, either the items of a single list or the arguments themselves.
+ */
+fn items(name: &str, values: &[Constant]) -> Result<Vec<Constant>, String> {
+    let items = match values {
+        [Constant::List(items)] => items.iter().cloned().map(Constant::numeric).collect::<Vec<Constant>>(),
+        _ => values.to_vec(),
+    };
+    if items.is_empty() {
+        return Err(format!("{} needs at least 1 number", name));
+    }
+    if items.iter().any(|item| !item.is_number()) {
+        return Err(format!("{} only works on numbers", name));
+    }
+    Ok(items)
+}
+
+/**
+ Adds up or multiplies numbers, whole numbers stay whole until they no longer fit in 64 bits.
+ * Nothing to add up is 0 and nothing to multiply is 1
+ */
+fn total(name: &str, items: &[Constant]) -> Result<Constant, String> {
+    if items.iter().any(|item| matches!(item, Constant::Float(_))) {
+        let floats = floats(items);
+        return Ok(Constant::Float(if name == "sum" { floats.iter().sum() } else { floats.iter().product() }));
+    }
+    let mut result: i64 = if name == "sum" { 0 } else { 1 };
+    for item in items {
+        let i: i64 = item.clone().into();
+        let next = if name == "sum" { result.checked_add(i) } else { result.checked_mul(i) };
+        result = next.ok_or(format!("The result of {} does not fit in 64 bits", name))?;
+    }
+    Ok(match items.first() {
+        Some(item) => item.with_value(result),
+        None => Constant::Integer(result),
+    })
+}
+
+/**
+ Sorts numbers of any kind by their value.
+ */
+fn sorted(mut items: Vec<Constant>) -> Vec<Constant> {
+    items.sort_by(|a, b| Into::<f64>::into(a.clone()).total_cmp(&b.clone().into()));
+    items
+}
+
+fn floats(items: &[Constant]) -> Vec<f64> {
+    items.iter().map(|item| item.clone().into()).collect()
+}
+
+fn mean(floats: &[f64]) -> f64 {
+    floats.iter().sum::<f64>() / floats.len() as f64
+}
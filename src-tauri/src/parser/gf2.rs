@@ -0,0 +1,42 @@
+/**
+ Carry-less product of two polynomials over GF(2), bit i holds the coefficient of x**i.
+ */
+pub fn clmul(a: u64, b: u64) -> u128 {
+    let mut result = 0u128;
+    for i in 0..64 {
+        if b >> i & 1 == 1 {
+            result ^= (a as u128) << i;
+        }
+    }
+    result
+}
+
+/**
+ Degree of a non zero polynomial.
+ */
+fn degree(a: u64) -> u32 {
+    63 - a.leading_zeros()
+}
+
+/**
+ Polynomial long division over GF(2), returns the quotient and the remainder.
+ * The divisor must not be zero
+ */
+pub fn divmod(a: u64, b: u64) -> (u64, u64) {
+    let (mut quotient, mut remainder) = (0u64, a);
+    let divisor = degree(b);
+    while remainder != 0 && degree(remainder) >= divisor {
+        let shift = degree(remainder) - divisor;
+        quotient |= 1 << shift;
+        remainder ^= b << shift;
+    }
+    (quotient, remainder)
+}
+
+/**
+ Reverses the lowest width bits of a, the bits above them are dropped.
+ * width has to be between 1 and 64
+ */
+pub fn reflect(a: u64, width: u32) -> u64 {
+    a.reverse_bits() >> (64 - width)
+}
@@ -0,0 +1,237 @@
+use super::constants::Constant;
+
+/**
+ Rows of a matrix, every row has the same length.
+ */
+pub type Matrix = Vec<Vec<f64>>;
+
+/**
+ Pivots smaller than this, relative to the largest entry, count as zero.
+ */
+const TOLERANCE: f64 = 1e-12;
+
+/**
+ Reads a matrix out of a constant.
+ * A list of numbers is a column vector and a list of equally long lists of numbers is a matrix
+ */
+pub fn from_constant(value: &Constant) -> Option<Matrix> {
+    match value {
+        Constant::Matrix(rows) => Some(rows.clone()),
+        Constant::List(items) if !items.is_empty() && items.iter().all(Constant::is_number) => {
+            Some(items.iter().map(|item| vec![item.clone().into()]).collect())
+        },
+        Constant::List(items) if !items.is_empty() => {
+            let rows = items.iter().map(|item| match item {
+                Constant::List(row) if row.iter().all(Constant::is_number) => Some(row.iter().map(|i| i.clone().into()).collect::<Vec<f64>>()),
+                _ => None,
+            }).collect::<Option<Matrix>>()?;
+            if rows[0].is_empty() || rows.iter().any(|row| row.len() != rows[0].len()) {
+                return None;
+            }
+            Some(rows)
+        },
+        _ => None,
+    }
+}
+
+/**
+ An entry taken out of a matrix, whole numbers become integers again.
+ */
+pub fn entry(i: f64) -> Constant {
+    if i.fract() == 0.0 && i.abs() < i64::MAX as f64 {
+        Constant::Integer(i as i64)
+    } else {
+        Constant::Float(i)
+    }
+}
+
+/**
+ Turns a single column back into a list, matrices that came from vectors are shown as vectors again.
+ */
+pub fn to_vector(matrix: Matrix) -> Constant {
+    Constant::List(matrix.into_iter().flatten().map(entry).collect())
+}
+
+/**
+ Applies +, - or * when either side is a matrix, None when neither is.
+ */
+pub fn combine(left: &Constant, right: &Constant, operator: &str) -> Result<Option<Constant>, String> {
+    if !matches!(left, Constant::Matrix(_)) && !matches!(right, Constant::Matrix(_)) {
+        return Ok(None);
+    }
+    //a number times a matrix scales every entry
+    if operator == "*" && (left.is_number() || right.is_number()) {
+        let (scale, matrix) = if left.is_number() { (left, right) } else { (right, left) };
+        let scale: f64 = scale.clone().into();
+        let matrix = from_constant(matrix).ok_or("Invalid matrix operation")?;
+        return Ok(Some(Constant::Matrix(matrix.into_iter().map(|row| row.into_iter().map(|i| i * scale).collect()).collect())));
+    }
+    let a = from_constant(left).ok_or("Invalid matrix operation")?;
+    let b = from_constant(right).ok_or("Invalid matrix operation")?;
+    Ok(Some(match operator {
+        "+" => Constant::Matrix(elementwise(&a, &b, |x, y| x + y)?),
+        "-" => Constant::Matrix(elementwise(&a, &b, |x, y| x - y)?),
+        //a list on the right is a column vector and one on the left a row vector
+        "*" if matches!(right, Constant::List(_)) => to_vector(multiply(&a, &b)?),
+        "*" if matches!(left, Constant::List(_)) => to_vector(multiply(&transpose(&a), &b)?),
+        "*" => Constant::Matrix(multiply(&a, &b)?),
+        _ => return Err("Invalid matrix operation".to_string()),
+    }))
+}
+
+fn elementwise(a: &Matrix, b: &Matrix, operation: fn(f64, f64) -> f64) -> Result<Matrix, String> {
+    if a.len() != b.len() || a[0].len() != b[0].len() {
+        return Err(format!("Can not combine a {}x{} matrix with a {}x{} matrix", a.len(), a[0].len(), b.len(), b[0].len()));
+    }
+    Ok(a.iter().zip(b).map(|(x, y)| x.iter().zip(y).map(|(x, y)| operation(*x, *y)).collect()).collect())
+}
+
+pub fn multiply(a: &Matrix, b: &Matrix) -> Result<Matrix, String> {
+    if a[0].len() != b.len() {
+        return Err(format!("Can not multiply a {}x{} matrix by a {}x{} matrix", a.len(), a[0].len(), b.len(), b[0].len()));
+    }
+    Ok(a.iter().map(|row| {
+        (0..b[0].len()).map(|j| row.iter().zip(b).map(|(x, b_row)| x * b_row[j]).sum()).collect()
+    }).collect())
+}
+
+pub fn transpose(a: &Matrix) -> Matrix {
+    (0..a[0].len()).map(|j| a.iter().map(|row| row[j]).collect()).collect()
+}
+
+pub fn identity(n: usize) -> Matrix {
+    (0..n).map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect()).collect()
+}
+
+fn is_square(a: &Matrix) -> bool {
+    a.len() == a[0].len()
+}
+
+/**
+ Largest entry of a matrix, used to scale the tolerance.
+ */
+fn scale(a: &Matrix) -> f64 {
+    let scale = a.iter().flatten().fold(0.0f64, |max, i| max.max(i.abs()));
+    if scale == 0.0 { 1.0 } else { scale }
+}
+
+/**
+ Determinant of a square matrix.
+ * Integer matrices use Bareiss elimination so the result is exact, everything else Gaussian elimination
+ */
+pub fn determinant(a: &Matrix) -> Result<Constant, String> {
+    if !is_square(a) {
+        return Err("det needs a square matrix".to_string());
+    }
+    if a.iter().flatten().all(|i| i.fract() == 0.0 && i.abs() < (1u64 << 53) as f64) {
+        let mut m: Vec<Vec<i128>> = a.iter().map(|row| row.iter().map(|i| *i as i128).collect()).collect();
+        let n = m.len();
+        let (mut sign, mut previous) = (1i128, 1i128);
+        for k in 0..n - 1 {
+            if m[k][k] == 0 {
+                match (k + 1..n).find(|i| m[*i][k] != 0) {
+                    Some(i) => {
+                        m.swap(k, i);
+                        sign = -sign;
+                    },
+                    None => return Ok(Constant::Integer(0)),
+                }
+            }
+            for i in k + 1..n {
+                for j in k + 1..n {
+                    m[i][j] = m[i][j].checked_mul(m[k][k]).zip(m[i][k].checked_mul(m[k][j]))
+                        .and_then(|(x, y)| x.checked_sub(y))
+                        .ok_or("The determinant does not fit in 64 bits")? / previous;
+                }
+            }
+            previous = m[k][k];
+        }
+        let result = sign * m[n - 1][n - 1];
+        return i64::try_from(result).map(Constant::Integer).map_err(|_| "The determinant does not fit in 64 bits".to_string());
+    }
+    let mut m = a.clone();
+    let n = m.len();
+    let mut result = 1.0;
+    for k in 0..n {
+        let pivot = (k..n).max_by(|x, y| m[*x][k].abs().total_cmp(&m[*y][k].abs())).unwrap();
+        if m[pivot][k] == 0.0 {
+            return Ok(Constant::Float(0.0));
+        }
+        if pivot != k {
+            m.swap(pivot, k);
+            result = -result;
+        }
+        result *= m[k][k];
+        let pivot_row = m[k].clone();
+        for row in m.iter_mut().skip(k + 1) {
+            let factor = row[k] / pivot_row[k];
+            for (x, p) in row.iter_mut().zip(&pivot_row).skip(k) {
+                *x -= factor * p;
+            }
+        }
+    }
+    Ok(Constant::Float(result))
+}
+
+/**
+ Reduces a matrix to row echelon form in place and returns the number of pivots.
+ * Only the first columns columns are used as pivots, the rest are carried along
+ */
+fn eliminate(m: &mut Matrix, columns: usize, tolerance: f64) -> usize {
+    let mut rank = 0;
+    for k in 0..columns {
+        if rank == m.len() {
+            break;
+        }
+        let pivot = (rank..m.len()).max_by(|x, y| m[*x][k].abs().total_cmp(&m[*y][k].abs())).unwrap();
+        if m[pivot][k].abs() <= tolerance {
+            continue;
+        }
+        m.swap(pivot, rank);
+        let divisor = m[rank][k];
+        for j in 0..m[rank].len() {
+            m[rank][j] /= divisor;
+        }
+        for i in 0..m.len() {
+            if i != rank && m[i][k] != 0.0 {
+                let factor = m[i][k];
+                for j in 0..m[i].len() {
+                    m[i][j] -= factor * m[rank][j];
+                }
+            }
+        }
+        rank += 1;
+    }
+    rank
+}
+
+pub fn rank(a: &Matrix) -> usize {
+    let mut m = a.clone();
+    let columns = m[0].len();
+    eliminate(&mut m, columns, TOLERANCE * scale(a))
+}
+
+/**
+ Solves a x = b for every column of b with Gauss-Jordan elimination.
+ */
+pub fn solve(a: &Matrix, b: &Matrix) -> Result<Matrix, String> {
+    if !is_square(a) {
+        return Err("Only square systems can be solved".to_string());
+    }
+    if a.len() != b.len() {
+        return Err(format!("A {}x{} system needs {} rows on the right side but got {}", a.len(), a.len(), a.len(), b.len()));
+    }
+    let n = a.len();
+    let mut m: Matrix = a.iter().zip(b).map(|(x, y)| x.iter().chain(y).copied().collect()).collect();
+    if eliminate(&mut m, n, TOLERANCE * scale(a)) < n {
+        return Err("The matrix is singular".to_string());
+    }
+    Ok(m.into_iter().map(|row| row[n..].to_vec()).collect())
+}
+
+pub fn inverse(a: &Matrix) -> Result<Matrix, String> {
+    if !is_square(a) {
+        return Err("inv needs a square matrix".to_string());
+    }
+    solve(a, &identity(a.len()))
+}
@@ -1,4 +1,13 @@
 pub mod constants;
+pub mod context;
 pub mod expressions;
 pub mod calculate;
-pub mod tokens;
\ No newline at end of file
+pub mod tokens;
+pub mod functions;
+pub mod number;
+pub mod gf2;
+pub mod matrix;
+pub mod session;
+pub mod format;
+pub mod symbolic;
+pub mod numeric;
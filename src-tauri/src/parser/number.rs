@@ -0,0 +1,219 @@
+/**
+ Witnesses that make Miller-Rabin deterministic for every 64 bit number.
+ */
+const WITNESSES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+/**
+ Greatest common divisor of the absolute values, gcd(0, 0) is 0.
+ */
+pub fn gcd(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/**
+ (a * b) mod m without overflowing.
+ */
+pub fn mul_mod(a: u64, b: u64, m: u64) -> u64 {
+    (a as u128 * b as u128 % m as u128) as u64
+}
+
+/**
+ (base ** exponent) mod m by repeated squaring.
+ */
+pub fn pow_mod(base: u64, exponent: u64, m: u64) -> u64 {
+    let mut result = 1 % m;
+    let mut base = base % m;
+    let mut exponent = exponent;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mul_mod(result, base, m);
+        }
+        base = mul_mod(base, base, m);
+        exponent >>= 1;
+    }
+    result
+}
+
+/**
+ Inverse of a modulo m using the extended Euclidean algorithm.
+ * Returns None when a and m are not coprime
+ */
+pub fn mod_inverse(a: i64, m: i64) -> Option<i64> {
+    let m = m as i128;
+    let (mut old_r, mut r) = ((a as i128).rem_euclid(m), m);
+    let (mut old_s, mut s) = (1i128, 0i128);
+    while r != 0 {
+        let quotient = old_r / r;
+        (old_r, r) = (r, old_r - quotient * r);
+        (old_s, s) = (s, old_s - quotient * s);
+    }
+    if old_r != 1 {
+        return None;
+    }
+    Some(old_s.rem_euclid(m) as i64)
+}
+
+/**
+ Deterministic Miller-Rabin primality test.
+ */
+pub fn is_prime(n: u64) -> bool {
+    if n < 2 {
+        return false;
+    }
+    for p in WITNESSES {
+        if n % p == 0 {
+            return n == p;
+        }
+    }
+    let mut d = n - 1;
+    let mut s = 0;
+    while d % 2 == 0 {
+        d /= 2;
+        s += 1;
+    }
+    'witness: for a in WITNESSES {
+        let mut x = pow_mod(a, d, n);
+        if x == 1 || x == n - 1 {
+            continue;
+        }
+        for _ in 1..s {
+            x = mul_mod(x, x, n);
+            if x == n - 1 {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+/**
+ Smallest prime greater than n, None when it does not fit in an i64.
+ */
+pub fn next_prime(n: i64) -> Option<i64> {
+    let mut candidate = n.max(1);
+    loop {
+        candidate = candidate.checked_add(1)?;
+        if is_prime(candidate as u64) {
+            return Some(candidate);
+        }
+    }
+}
+
+/**
+ Finds a non trivial divisor of a composite number with Pollard's rho.
+ */
+fn pollard_rho(n: u64) -> u64 {
+    if n % 2 == 0 {
+        return 2;
+    }
+    let mut c = 1u64;
+    loop {
+        let f = |x: u64| ((x as u128 * x as u128 + c as u128) % n as u128) as u64;
+        let (mut x, mut y, mut d) = (2u64, 2u64, 1u64);
+        while d == 1 {
+            x = f(x);
+            y = f(f(y));
+            d = gcd(x.abs_diff(y) as i128, n as i128) as u64;
+        }
+        if d != n {
+            return d;
+        }
+        c += 1;
+    }
+}
+
+fn collect_primes(n: u64, primes: &mut Vec<u64>) {
+    if n == 1 {
+        return;
+    }
+    if is_prime(n) {
+        primes.push(n);
+        return;
+    }
+    let divisor = pollard_rho(n);
+    collect_primes(divisor, primes);
+    collect_primes(n / divisor, primes);
+}
+
+/**
+ Prime factorization of n as (prime, exponent) pairs in ascending order.
+ */
+pub fn factorize(n: u64) -> Vec<(u64, u32)> {
+    let mut primes = Vec::new();
+    collect_primes(n, &mut primes);
+    primes.sort_unstable();
+    let mut factors: Vec<(u64, u32)> = Vec::new();
+    for prime in primes {
+        match factors.last_mut() {
+            Some((last, exponent)) if *last == prime => *exponent += 1,
+            _ => factors.push((prime, 1)),
+        }
+    }
+    factors
+}
+
+/**
+ Terms of the continued fraction of x, at most terms of them.
+ * Stops once the convergent is x as far as a float can tell
+ */
+pub fn continued_fraction(x: f64, terms: usize) -> Vec<i64> {
+    let mut result = Vec::new();
+    let (mut p0, mut q0, mut p1, mut q1) = (0.0, 1.0, 1.0, 0.0);
+    let mut y = x;
+    while result.len() < terms {
+        let a = y.floor();
+        if a.abs() >= (1u64 << 53) as f64 {
+            break;
+        }
+        result.push(a as i64);
+        (p0, q0, p1, q1) = (p1, q1, a * p1 + p0, a * q1 + q0);
+        if y == a || (p1 / q1 - x).abs() <= f64::EPSILON * x.abs() {
+            break;
+        }
+        y = 1.0 / (y - a);
+    }
+    result
+}
+
+/**
+ Best rational approximation of x whose denominator is at most max_denominator, as (numerator, denominator).
+ * Walks the convergents of the continued fraction and stops early at the first one within tolerance
+ * Returns None for numbers too large to be told apart from an integer
+ */
+pub fn rational(x: f64, max_denominator: i64, tolerance: f64) -> Option<(i64, i64)> {
+    if !x.is_finite() || x.abs() >= (1u64 << 53) as f64 {
+        return None;
+    }
+    let max = max_denominator.max(1) as i128;
+    let (mut p0, mut q0, mut p1, mut q1) = (0i128, 1i128, 1i128, 0i128);
+    let mut y = x;
+    loop {
+        let a = y.floor();
+        if a.abs() >= (1u64 << 53) as f64 {
+            break;
+        }
+        let a = a as i128;
+        let (p2, q2) = (a * p1 + p0, a * q1 + q0);
+        if q2 > max {
+            //the best semiconvergent under the bound can still beat the last convergent
+            let k = (max - q0) / q1;
+            let (p, q) = (p0 + k * p1, q0 + k * q1);
+            if (p as f64 / q as f64 - x).abs() < (p1 as f64 / q1 as f64 - x).abs() {
+                (p1, q1) = (p, q);
+            }
+            break;
+        }
+        (p0, q0, p1, q1) = (p1, q1, p2, q2);
+        let fraction = y - a as f64;
+        if fraction == 0.0 || (p1 as f64 / q1 as f64 - x).abs() <= tolerance {
+            break;
+        }
+        y = 1.0 / fraction;
+    }
+    Some((i64::try_from(p1).ok()?, q1 as i64))
+}
@@ -0,0 +1,415 @@
+use serde::Serialize;
+
+use super::{constants::Constant, context::Context, expressions::{BinaryExpression, EnumExpression, Expression}, matrix};
+
+/**
+ Points per unit interval that root scans, and per decade on each side of 0 when no interval is given.
+ */
+const SCAN_POINTS: usize = 1000;
+const POINTS_PER_DECADE: usize = 50;
+
+/**
+ Most terms sum and prod add up, and most pieces integrate splits the interval into.
+ */
+const MAX_TERMS: i64 = 1_000_000;
+const MAX_SEGMENTS: usize = 1000;
+
+/**
+ Most points a sample can have, and how often a suspicious step is halved to tell a jump from a steep slope.
+ */
+const MAX_SAMPLES: usize = 100_000;
+const JUMP_HALVINGS: usize = 40;
+
+/**
+ The 15 point Kronrod rule and the 7 point Gauss rule inside it, for half of the symmetric nodes.
+ * Gauss uses every other Kronrod node starting from the second, the difference of both is the error estimate
+ */
+const KRONROD_NODES: [f64; 8] = [
+    0.991_455_371_120_812_6, 0.949_107_912_342_758_5, 0.864_864_423_359_769_1, 0.741_531_185_599_394_4,
+    0.586_087_235_467_691_1, 0.405_845_151_377_397_2, 0.207_784_955_007_898_5, 0.0,
+];
+const KRONROD_WEIGHTS: [f64; 8] = [
+    0.022_935_322_010_529_225, 0.063_092_092_629_978_55, 0.104_790_010_322_250_18, 0.140_653_259_715_525_92,
+    0.169_004_726_639_267_9, 0.190_350_578_064_785_4, 0.204_432_940_075_298_9, 0.209_482_141_084_727_83,
+];
+const GAUSS_WEIGHTS: [f64; 4] = [0.129_484_966_168_869_7, 0.279_705_391_489_276_7, 0.381_830_050_505_118_9, 0.417_959_183_673_469_4];
+
+/**
+ An expression seen as a function of one of its variables.
+ * The expression is parsed once and evaluated again for every x, the context is reused between calls
+ */
+pub struct Curve<'a> {
+    expression: &'a EnumExpression,
+    variable: String,
+    context: Context,
+}
+
+impl<'a> Curve<'a> {
+    pub fn new(expression: &'a EnumExpression, variable: &str, context: &Context) -> Self {
+        Self { expression, variable: variable.to_string(), context: context.without_modulus() }
+    }
+
+    /**
+     The value at x, an error when it does not evaluate to a single number
+     */
+    pub fn at(&mut self, x: f64) -> Result<f64, String> {
+        self.context.variables.insert(self.variable.clone(), Constant::Float(x));
+        let value = self.expression.evaluate(&self.context)?.numeric();
+        if !value.is_number() {
+            return Err("The expression has to give a single number".to_string());
+        }
+        Ok(value.into())
+    }
+
+    /**
+     Like at, but a point that fails counts as NaN so a scan can step over it
+     */
+    fn value(&mut self, x: f64) -> f64 {
+        self.at(x).unwrap_or(f64::NAN)
+    }
+}
+
+/**
+ One point of a sample, y is an error where the expression can not be evaluated or is not finite.
+ */
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Point {
+    pub x: f64,
+    pub y: Result<f64, String>,
+}
+
+/**
+ Evenly spaced points of a curve for plotting.
+ * A break at i means the curve jumps between points i and i + 1, at a pole or a step, and should not be drawn as a line there
+ */
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Sample {
+    pub points: Vec<Point>,
+    pub breaks: Vec<usize>,
+}
+
+/**
+ Samples the expression at n evenly spaced points from from to to, both included.
+ * A point that fails only fails itself, the rest of the sample goes on
+ */
+pub fn sample(expression: &EnumExpression, variable: &str, from: f64, to: f64, n: usize, context: &Context) -> Result<Sample, String> {
+    if !from.is_finite() || !to.is_finite() {
+        return Err("A sample needs a finite interval".to_string());
+    }
+    if !(2..=MAX_SAMPLES).contains(&n) {
+        return Err(format!("A sample needs between 2 and {} points", MAX_SAMPLES));
+    }
+    let mut curve = Curve::new(expression, variable, context);
+    let points: Vec<Point> = (0..n).map(|i| {
+        let x = from + (to - from) * i as f64 / (n - 1) as f64;
+        let y = curve.at(x).and_then(|y| if y.is_finite() { Ok(y) } else { Err("The value is not finite".to_string()) });
+        Point { x, y }
+    }).collect();
+    //a step that is much larger than the ones next to it is looked at closer, everything else is taken as continuous
+    let steps: Vec<Option<f64>> = points.windows(2).map(|pair| match (&pair[0].y, &pair[1].y) {
+        (Ok(a), Ok(b)) => Some((b - a).abs()),
+        _ => None,
+    }).collect();
+    let mut breaks = Vec::new();
+    for (i, step) in steps.iter().enumerate() {
+        let step = match step {
+            Some(step) => *step,
+            None => continue,
+        };
+        let before = if i > 0 { steps[i - 1].unwrap_or(0.0) } else { 0.0 };
+        let after = steps.get(i + 1).copied().flatten().unwrap_or(0.0);
+        if step > 2.0 * before.max(after) && jumps(&mut curve, &points[i], &points[i + 1]) {
+            breaks.push(i);
+        }
+    }
+    Ok(Sample { points, breaks })
+}
+
+/**
+ Whether the curve jumps between two points.
+ * The half with the larger change is kept, a continuous curve changes less and less while a jump keeps its size
+ */
+fn jumps(curve: &mut Curve, from: &Point, to: &Point) -> bool {
+    let (mut a, mut fa) = (from.x, from.y.clone().unwrap_or(f64::NAN));
+    let (mut b, mut fb) = (to.x, to.y.clone().unwrap_or(f64::NAN));
+    let step = (fb - fa).abs();
+    for _ in 0..JUMP_HALVINGS {
+        let m = a + (b - a) / 2.0;
+        let fm = curve.value(m);
+        if !fm.is_finite() {
+            return true;
+        }
+        if (fm - fa).abs() > (fb - fm).abs() {
+            (b, fb) = (m, fm);
+        } else {
+            (a, fa) = (m, fm);
+        }
+    }
+    (fb - fa).abs() > 1e-3 * step
+}
+
+/**
+ The name given as the variable argument of solve, root, integrate and the like.
+ */
+pub fn variable<'a>(name: &str, argument: &'a EnumExpression) -> Result<&'a str, String> {
+    match argument {
+        EnumExpression::Variable(variable) => Ok(&variable.name),
+        _ => Err(format!("{} needs a variable name as its second argument", name)),
+    }
+}
+
+/**
+ A bound of an interval, evaluated like any other argument.
+ */
+pub fn bound(name: &str, argument: &EnumExpression, context: &Context) -> Result<f64, String> {
+    let value = argument.evaluate(context)?.numeric();
+    if !value.is_number() {
+        return Err(format!("{} needs numbers as the ends of its interval", name));
+    }
+    let value: f64 = value.into();
+    if !value.is_finite() {
+        return Err(format!("{} needs a finite interval", name));
+    }
+    Ok(value)
+}
+
+/**
+ solve(left == right, x) and solve(left == right, x, a, b), every x in the interval where both sides agree.
+ * Without an interval the whole range from -1e12 to 1e12 is searched
+ */
+pub fn solve(arguments: &[EnumExpression], context: &Context) -> Result<Constant, String> {
+    let interval = match arguments {
+        [_, _] => None,
+        [_, _, a, b] => Some((bound("solve", a, context)?, bound("solve", b, context)?)),
+        _ => return Err(format!("solve takes 2 or 4 arguments but got {}", arguments.len())),
+    };
+    //an equation is solved as left - right == 0, anything else is taken as equal to 0
+    let function = match &arguments[0] {
+        EnumExpression::Binary(BinaryExpression::Equal(equal)) => {
+            EnumExpression::Binary(BinaryExpression::new(equal.left.clone(), equal.right.clone(), "-"))
+        },
+        expression => expression.clone(),
+    };
+    let mut curve = Curve::new(&function, variable("solve", &arguments[1])?, context);
+    Ok(to_list(roots(&mut curve, interval)))
+}
+
+/**
+ root(f, x, a, b), every x in [a, b] where f is 0.
+ */
+pub fn root(arguments: &[EnumExpression], context: &Context) -> Result<Constant, String> {
+    if arguments.len() != 4 {
+        return Err(format!("root takes 4 arguments but got {}", arguments.len()));
+    }
+    let interval = (bound("root", &arguments[2], context)?, bound("root", &arguments[3], context)?);
+    let mut curve = Curve::new(&arguments[0], variable("root", &arguments[1])?, context);
+    Ok(to_list(roots(&mut curve, Some(interval))))
+}
+
+/**
+ integrate(f, x, a, b), the area under f from a to b.
+ */
+pub fn integrate(arguments: &[EnumExpression], context: &Context) -> Result<Constant, String> {
+    quadrature("integrate", arguments, context).map(|(value, _)| Constant::Float(value))
+}
+
+/**
+ integrate_err(f, x, a, b), the area under f from a to b along with an estimate of its error, as [value, error].
+ */
+pub fn integrate_err(arguments: &[EnumExpression], context: &Context) -> Result<Constant, String> {
+    let (value, error) = quadrature("integrate_err", arguments, context)?;
+    Ok(Constant::List(vec![Constant::Float(value), Constant::Float(error)]))
+}
+
+/**
+ The integral of integrate and integrate_err and the estimate of its error.
+ * The interval is split where the error estimate is largest until the estimates add up to about 1e-10 of the result
+ * An integral that does not get there, like one over a pole, is an error that gives the estimate that was reached
+ */
+fn quadrature(name: &str, arguments: &[EnumExpression], context: &Context) -> Result<(f64, f64), String> {
+    if arguments.len() != 4 {
+        return Err(format!("{} takes 4 arguments but got {}", name, arguments.len()));
+    }
+    let (a, b) = (bound(name, &arguments[2], context)?, bound(name, &arguments[3], context)?);
+    let mut curve = Curve::new(&arguments[0], variable(name, &arguments[1])?, context);
+    let mut segments = vec![kronrod(&mut curve, a, b)?];
+    loop {
+        let value: f64 = segments.iter().map(|segment| segment.2).sum();
+        let error: f64 = segments.iter().map(|segment| segment.3).sum();
+        if !value.is_finite() || !error.is_finite() {
+            return Err("The integral does not converge".to_string());
+        }
+        if error <= (1e-10 * value.abs()).max(1e-13) {
+            return Ok((value, error));
+        }
+        if segments.len() >= MAX_SEGMENTS {
+            return Err(format!("The integral did not converge, it is about {} give or take {}", value, error));
+        }
+        let worst = (0..segments.len()).max_by(|i, j| segments[*i].3.total_cmp(&segments[*j].3)).unwrap_or(0);
+        let (a, b, _, _) = segments.swap_remove(worst);
+        let middle = a + (b - a) / 2.0;
+        segments.push(kronrod(&mut curve, a, middle)?);
+        segments.push(kronrod(&mut curve, middle, b)?);
+    }
+}
+
+/**
+ The integral over [a, b] as (a, b, value, error estimate).
+ * The ends themselves are never evaluated, so a pole right at an end can still be integrated
+ */
+fn kronrod(curve: &mut Curve, a: f64, b: f64) -> Result<(f64, f64, f64, f64), String> {
+    let (center, half) = (a + (b - a) / 2.0, (b - a) / 2.0);
+    let (mut kronrod, mut gauss) = (0.0, 0.0);
+    for (i, node) in KRONROD_NODES.iter().enumerate() {
+        let y = if *node == 0.0 {
+            curve.at(center)?
+        } else {
+            curve.at(center - half * node)? + curve.at(center + half * node)?
+        };
+        kronrod += KRONROD_WEIGHTS[i] * y;
+        if i % 2 == 1 {
+            gauss += GAUSS_WEIGHTS[i / 2] * y;
+        }
+    }
+    Ok((a, b, kronrod * half, ((kronrod - gauss) * half).abs()))
+}
+
+/**
+ Whether sum and prod get an expression over a variable, like sum(k ** 2, k, 1, 10), rather than 4 numbers to add up.
+ * The shape decides, so a term that leaves the variable out like sum(2, k, 1, 10) is a series too
+ */
+pub fn is_series(arguments: &[EnumExpression]) -> bool {
+    matches!(arguments, [_, EnumExpression::Variable(_), _, _])
+}
+
+/**
+ The terms of sum(f, k, a, b) and prod(f, k, a, b), f for every whole k from a to b.
+ * A range that runs backwards has no terms
+ */
+pub fn terms(name: &str, arguments: &[EnumExpression], context: &Context) -> Result<Vec<Constant>, String> {
+    let variable = variable(name, &arguments[1])?;
+    let (from, to) = (whole(name, &arguments[2], context)?, whole(name, &arguments[3], context)?);
+    //the ends can be as far apart as the whole range of i64
+    if to as i128 - from as i128 >= MAX_TERMS as i128 {
+        return Err(format!("{} can not take more than {} terms", name, MAX_TERMS));
+    }
+    let mut context = context.clone();
+    let mut terms = Vec::new();
+    for k in from..=to {
+        context.variables.insert(variable.to_string(), Constant::Integer(k));
+        let term = arguments[0].evaluate(&context)?.numeric();
+        if !term.is_number() {
+            return Err(format!("{} only works on numbers", name));
+        }
+        terms.push(term);
+    }
+    Ok(terms)
+}
+
+fn whole(name: &str, argument: &EnumExpression, context: &Context) -> Result<i64, String> {
+    match argument.evaluate(context)?.numeric() {
+        Constant::Float(f) if f.fract() == 0.0 && f.abs() < i64::MAX as f64 => Ok(f as i64),
+        value if value.is_number() && !matches!(value, Constant::Float(_)) => Ok(value.into()),
+        _ => Err(format!("{} needs whole numbers as the ends of its range", name)),
+    }
+}
+
+fn to_list(roots: Vec<f64>) -> Constant {
+    Constant::List(roots.into_iter().map(matrix::entry).collect())
+}
+
+/**
+ Points to look for sign changes at, evenly spaced in an interval and spread over every magnitude without one.
+ */
+fn grid(interval: Option<(f64, f64)>) -> Vec<f64> {
+    match interval {
+        Some((a, b)) => (0..=SCAN_POINTS).map(|i| a + (b - a) * i as f64 / SCAN_POINTS as f64).collect(),
+        None => {
+            let decades = 18 * POINTS_PER_DECADE;
+            let positive: Vec<f64> = (0..=decades).map(|i| 10f64.powf(-6.0 + i as f64 / POINTS_PER_DECADE as f64)).collect();
+            positive.iter().rev().map(|x| -x).chain(std::iter::once(0.0)).chain(positive.iter().copied()).collect()
+        },
+    }
+}
+
+/**
+ Every real root that a scan can find.
+ * Sign changes are narrowed down by bisection, a pole changes sign too but does not come near 0 and is dropped
+ * Roots that only touch 0, like the one of x ** 2, are found with Newton's method from where |f| is smallest
+ */
+fn roots(curve: &mut Curve, interval: Option<(f64, f64)>) -> Vec<f64> {
+    let interval = interval.map(|(a, b)| if a <= b { (a, b) } else { (b, a) });
+    let xs = grid(interval);
+    let ys: Vec<f64> = xs.iter().map(|x| curve.value(*x)).collect();
+    let mut roots = Vec::new();
+    for i in 0..xs.len() {
+        if ys[i] == 0.0 {
+            roots.push(xs[i]);
+            continue;
+        }
+        //a sign change next to an infinite value is a pole, not a root
+        if i + 1 < xs.len() && ys[i].is_finite() && ys[i + 1].is_finite() && ys[i] * ys[i + 1] < 0.0 {
+            if let Some(root) = bisect(curve, (xs[i], ys[i]), (xs[i + 1], ys[i + 1])) {
+                roots.push(root);
+            }
+        }
+        let smallest = i > 0 && i + 1 < xs.len() && ys[i].abs() < ys[i - 1].abs() && ys[i].abs() < ys[i + 1].abs();
+        if smallest && ys[i] * ys[i - 1] > 0.0 && ys[i] * ys[i + 1] > 0.0 {
+            match newton(curve, xs[i]) {
+                Some(root) if interval.map_or(true, |(a, b)| (a..=b).contains(&root)) => roots.push(root),
+                _ => {},
+            }
+        }
+    }
+    roots.sort_by(f64::total_cmp);
+    roots.dedup_by(|a, b| (*a - *b).abs() <= 1e-9 * a.abs().max(1.0));
+    //a root that is a whole number up to rounding is shown as one
+    roots.into_iter().map(|root| if (root - root.round()).abs() <= 1e-9 * root.abs().max(1.0) { root.round() } else { root }).collect()
+}
+
+fn bisect(curve: &mut Curve, (mut a, mut fa): (f64, f64), (mut b, fb): (f64, f64)) -> Option<f64> {
+    let scale = fa.abs().max(fb.abs()).max(1.0);
+    for _ in 0..200 {
+        let m = a + (b - a) / 2.0;
+        if m == a || m == b {
+            break;
+        }
+        let fm = curve.value(m);
+        if !fm.is_finite() {
+            return None;
+        }
+        if fm == 0.0 {
+            return Some(m);
+        }
+        if (fm < 0.0) == (fa < 0.0) {
+            (a, fa) = (m, fm);
+        } else {
+            b = m;
+        }
+    }
+    let root = a + (b - a) / 2.0;
+    if curve.value(root).abs() <= 1e-6 * scale { Some(root) } else { None }
+}
+
+fn newton(curve: &mut Curve, mut x: f64) -> Option<f64> {
+    for _ in 0..100 {
+        let fx = curve.value(x);
+        if fx == 0.0 {
+            return Some(x);
+        }
+        let h = 1e-7 * x.abs().max(1.0);
+        let slope = (curve.value(x + h) - curve.value(x - h)) / (2.0 * h);
+        if !slope.is_finite() || slope == 0.0 {
+            break;
+        }
+        let step = fx / slope;
+        x -= step;
+        if !x.is_finite() {
+            return None;
+        }
+        if step.abs() <= 1e-15 * x.abs().max(1.0) {
+            break;
+        }
+    }
+    if curve.value(x).abs() <= 1e-9 { Some(x) } else { None }
+}
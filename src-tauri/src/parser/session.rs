@@ -0,0 +1,155 @@
+use std::sync::Arc;
+
+use super::{calculate::{Implicit, Parser}, constants::{Constant, Target}, context::Context, expressions::{Expression, Lambda}, tokens::Token};
+use logos::Logos;
+use serde::Serialize;
+
+/**
+ The outcome of one statement of a script, line counts from 1 and is where the statement starts.
+ */
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Statement {
+    pub line: usize,
+    pub input: String,
+    pub result: Result<Constant, String>,
+}
+
+/**
+ Runs statements one after another and keeps the variables and functions they define.
+ */
+#[derive(Default)]
+pub struct Session {
+    pub context: Context,
+    pub implicit: Implicit,
+}
+
+impl Session {
+    pub fn new(context: Context) -> Self {
+        Self { context, implicit: Implicit::default() }
+    }
+
+    /**
+     Runs a single statement, an expression, an assignment like x = 2 or a definition like f(x) = x * 2.
+     * The value is also kept as ans, it is turned into the target when there is one and left as it is otherwise
+     */
+    pub fn run(&mut self, statement: &str, target: impl Into<Option<Target>>) -> Result<Constant, String> {
+        let value = match Self::assignment(statement) {
+            Some((name, Some(parameters), expression)) => {
+                let body = Parser::with_implicit(expression, self.implicit).parse()?;
+                let value = Constant::Function(Lambda {
+                    parameters,
+                    body: Arc::new(body),
+                    captured: self.context.variables.clone(),
+                });
+                self.context.variables.insert(name, value.clone());
+                value
+            },
+            Some((name, None, expression)) => {
+                let value = Parser::with_implicit(expression, self.implicit).parse()?.evaluate(&self.context)?;
+                self.context.variables.insert(name, value.clone());
+                value
+            },
+            None => Parser::with_implicit(statement, self.implicit).parse()?.evaluate(&self.context)?,
+        };
+        self.context.variables.insert("ans".to_string(), value.clone());
+        Ok(match target.into() {
+            Some(target) => value.into_target_constant(target),
+            None => value,
+        })
+    }
+
+    /**
+     Runs every statement of a script in order, a failing statement does not stop the ones after it.
+     */
+    pub fn run_script(&mut self, script: &str, target: impl Into<Option<Target>>) -> Vec<Statement> {
+        let target = target.into();
+        Self::statements(script).into_iter().map(|(line, input)| {
+            let result = self.run(&input, target);
+            Statement { line, input, result }
+        }).collect()
+    }
+
+    /**
+     Whether a script can be run as it is, false while a bracket is still open and more lines have to follow.
+     */
+    pub fn is_complete(script: &str) -> bool {
+        let (mut depth, mut comment) = (0i32, false);
+        for c in script.chars() {
+            match c {
+                '\n' => comment = false,
+                _ if comment => {},
+                '#' => comment = true,
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' => depth -= 1,
+                _ => {},
+            }
+        }
+        depth <= 0
+    }
+
+    /**
+     Splits name = expression and name(parameters) = expression into their parts, None for anything else.
+     */
+    fn assignment(statement: &str) -> Option<(String, Option<Vec<String>>, &str)> {
+        let mut lexer = Token::lexer(statement);
+        let name = match lexer.next()?.ok()? {
+            Token::Identifier(name) => name,
+            _ => return None,
+        };
+        let parameters = match lexer.next()?.ok()? {
+            Token::Assign => None,
+            Token::LeftParenthesis => {
+                let parameters = Parser::parameters(&lexer, Token::Assign)?;
+                //skip the names, the commas between them, the closing parenthesis and the =
+                for _ in 0..(2 * parameters.len()).max(1) + 1 {
+                    lexer.next();
+                }
+                Some(parameters)
+            },
+            _ => return None,
+        };
+        Some((name, parameters, &statement[lexer.span().end..]))
+    }
+
+    /**
+     Splits a script into statements along with the line each one starts on.
+     * Newlines and semicolons end a statement unless a bracket is still open, # starts a comment until the end of the line
+     */
+    fn statements(script: &str) -> Vec<(usize, String)> {
+        let mut statements = Vec::new();
+        let mut current = String::new();
+        let (mut line, mut start, mut depth, mut comment) = (1, 1, 0i32, false);
+        for c in script.chars() {
+            match c {
+                '\n' => comment = false,
+                _ if comment => continue,
+                '#' => {
+                    comment = true;
+                    continue;
+                },
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' => depth -= 1,
+                _ => {},
+            }
+            if (c == '\n' || c == ';') && depth <= 0 {
+                if !current.trim().is_empty() {
+                    statements.push((start, current.trim().to_string()));
+                }
+                current.clear();
+                depth = 0;
+            } else {
+                if current.trim().is_empty() && !c.is_whitespace() {
+                    start = line;
+                }
+                current.push(c);
+            }
+            if c == '\n' {
+                line += 1;
+            }
+        }
+        if !current.trim().is_empty() {
+            statements.push((start, current.trim().to_string()));
+        }
+        statements
+    }
+}
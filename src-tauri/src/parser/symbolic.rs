@@ -0,0 +1,545 @@
+use std::{cmp::Ordering, fmt};
+
+use super::{calculate::precedence, constants::Constant, context::Context, expressions::{BinaryExpression, CallExpression, ConstExpression, EnumExpression, UnaryExpression, VariableExpression}, format::FormatOptions, functions, number, tokens::Token};
+
+/**
+ Functions that diff knows the derivative of and simplify can fold when their argument is a number.
+ */
+const ELEMENTARY: [&str; 6] = ["sin", "cos", "tan", "exp", "ln", "sqrt"];
+
+/**
+ The normal form that simplification works on.
+ * Differences are sums with a factor of -1 and quotients are products with a power of -1
+ */
+#[derive(Clone, Debug, PartialEq)]
+enum Term {
+    Number(f64),
+    Symbol(String),
+    Sum(Vec<Term>),
+    Product(Vec<Term>),
+    Power(Box<Term>, Box<Term>),
+    Call(String, Vec<Term>),
+}
+
+/**
+ simplify(expression), folds constants and collects like terms.
+ * Variables with a value are replaced by it and the others stay symbolic
+ */
+pub fn simplify(arguments: &[EnumExpression], context: &Context) -> Result<Constant, String> {
+    if arguments.len() != 1 {
+        return Err(format!("simplify takes 1 arguments but got {}", arguments.len()));
+    }
+    Ok(to_constant(reduce(from_expression(&arguments[0], context, None)?)))
+}
+
+/**
+ diff(expression, x), the simplified derivative of the expression with respect to x.
+ */
+pub fn diff(arguments: &[EnumExpression], context: &Context) -> Result<Constant, String> {
+    let variable = match arguments {
+        [_, EnumExpression::Variable(variable)] => &variable.name,
+        [_, _] => return Err("diff needs a variable name as its second argument".to_string()),
+        _ => return Err(format!("diff takes 2 arguments but got {}", arguments.len())),
+    };
+    let term = reduce(from_expression(&arguments[0], context, Some(variable))?);
+    Ok(to_constant(reduce(derivative(&term, variable)?)))
+}
+
+fn to_constant(term: Term) -> Constant {
+    match term {
+        Term::Number(n) => number_constant(n),
+        term => Constant::Expression(Box::new(to_expression(&term))),
+    }
+}
+
+fn number_constant(n: f64) -> Constant {
+    if n.fract() == 0.0 && n.abs() < (1u64 << 53) as f64 {
+        Constant::Integer(n as i64)
+    } else {
+        Constant::Float(n)
+    }
+}
+
+/**
+ Reads an expression tree into a term, keep is the variable that is never replaced by its value.
+ */
+fn from_expression(expression: &EnumExpression, context: &Context, keep: Option<&str>) -> Result<Term, String> {
+    let term = |expression: &EnumExpression| from_expression(expression, context, keep);
+    Ok(match expression {
+        EnumExpression::Constant(constant) => from_constant(&constant.constant, context, keep)?,
+        EnumExpression::Variable(variable) => match context.variables.get(&variable.name) {
+            Some(value) if keep != Some(variable.name.as_str()) => from_constant(value, context, keep)?,
+            _ => Term::Symbol(variable.name.clone()),
+        },
+        EnumExpression::Unary(UnaryExpression::Negate(negate)) => Term::Product(vec![Term::Number(-1.0), term(&negate.expression)?]),
+        EnumExpression::Unary(UnaryExpression::Percent(percent)) => Term::Product(vec![Term::Number(0.01), term(&percent.expression)?]),
+        EnumExpression::Binary(binary) => {
+            let (left, right) = binary.operands();
+            let (left, right) = (term(left)?, term(right)?);
+            match binary.operator() {
+                "+" => Term::Sum(vec![left, right]),
+                "-" => Term::Sum(vec![left, Term::Product(vec![Term::Number(-1.0), right])]),
+                "*" => Term::Product(vec![left, right]),
+                //symbolic division is always true division
+                "/" | "/." => Term::Product(vec![left, Term::Power(Box::new(right), Box::new(Term::Number(-1.0)))]),
+                "**" => Term::Power(Box::new(left), Box::new(right)),
+                "√" => Term::Power(Box::new(left), Box::new(Term::Power(Box::new(right), Box::new(Term::Number(-1.0))))),
+                operator => return Err(format!("{} can not be used symbolically", operator)),
+            }
+        },
+        //nested symbolic calls are worked out first, diff(diff(x ** 3, x), x)
+        EnumExpression::Call(call) if call.name == "diff" || call.name == "simplify" => {
+            from_constant(&functions::call(&call.name, &call.arguments, context)?, context, keep)?
+        },
+        EnumExpression::Call(call) => Term::Call(call.name.clone(), call.arguments.iter().map(term).collect::<Result<Vec<Term>, String>>()?),
+        _ => return Err("Only arithmetic, powers and function calls can be used symbolically".to_string()),
+    })
+}
+
+fn from_constant(constant: &Constant, context: &Context, keep: Option<&str>) -> Result<Term, String> {
+    match constant {
+        Constant::Expression(expression) => from_expression(expression, context, keep),
+        constant if constant.is_number() => Ok(Term::Number(constant.clone().numeric().into())),
+        _ => Err("Only numbers can be used symbolically".to_string()),
+    }
+}
+
+/**
+ Text a term is sorted by, so equal sums and products always come out in the same order.
+ */
+fn key(term: &Term) -> String {
+    to_expression(term).to_string()
+}
+
+/**
+ Degree of a term, higher powers come first in a sum.
+ */
+fn degree(term: &Term) -> f64 {
+    match term {
+        Term::Number(_) => 0.0,
+        Term::Symbol(_) | Term::Call(_, _) => 1.0,
+        Term::Power(base, exponent) => match exponent.as_ref() {
+            Term::Number(n) => degree(base) * n,
+            _ => 1.0,
+        },
+        Term::Product(factors) => factors.iter().map(degree).sum(),
+        Term::Sum(terms) => terms.iter().map(degree).fold(0.0, f64::max),
+    }
+}
+
+fn compare(a: &Term, b: &Term) -> Ordering {
+    degree(b).total_cmp(&degree(a)).then_with(|| key(a).cmp(&key(b)))
+}
+
+/**
+ Splits a term into its numeric coefficient and the rest, 3 * x is (3, x).
+ */
+fn coefficient(term: Term) -> (f64, Option<Term>) {
+    match term {
+        Term::Number(n) => (n, None),
+        Term::Product(mut factors) => match factors.first() {
+            Some(Term::Number(n)) => {
+                let n = *n;
+                factors.remove(0);
+                (n, Some(if factors.len() == 1 { factors.remove(0) } else { Term::Product(factors) }))
+            },
+            _ => (1.0, Some(Term::Product(factors))),
+        },
+        term => (1.0, Some(term)),
+    }
+}
+
+/**
+ Simplifies a term bottom up.
+ */
+fn reduce(term: Term) -> Term {
+    match term {
+        Term::Sum(terms) => reduce_sum(terms.into_iter().map(reduce).collect()),
+        Term::Product(factors) => reduce_product(factors.into_iter().map(reduce).collect()),
+        Term::Power(base, exponent) => reduce_power(reduce(*base), reduce(*exponent)),
+        Term::Call(name, arguments) => {
+            let arguments: Vec<Term> = arguments.into_iter().map(reduce).collect();
+            if let (true, [Term::Number(n)]) = (ELEMENTARY.contains(&name.as_str()), arguments.as_slice()) {
+                let value = match name.as_str() {
+                    "sin" => n.sin(),
+                    "cos" => n.cos(),
+                    "tan" => n.tan(),
+                    "exp" => n.exp(),
+                    "ln" => n.ln(),
+                    _ => n.sqrt(),
+                };
+                //only whole results are folded, ln(2) stays exact while ln(1) becomes 0
+                if value.is_finite() && value.fract() == 0.0 {
+                    return Term::Number(value);
+                }
+            }
+            Term::Call(name, arguments)
+        },
+        term => term,
+    }
+}
+
+fn reduce_sum(terms: Vec<Term>) -> Term {
+    //collect the coefficients of equal terms, 2x + 3x is 5x
+    let mut constant = 0.0;
+    let mut like: Vec<(Term, f64)> = Vec::new();
+    let mut pending = terms;
+    while let Some(term) = pending.pop() {
+        match term {
+            Term::Sum(inner) => pending.extend(inner),
+            term => match coefficient(term) {
+                (n, None) => constant += n,
+                (n, Some(rest)) => match like.iter_mut().find(|(other, _)| *other == rest) {
+                    Some((_, sum)) => *sum += n,
+                    None => like.push((rest, n)),
+                },
+            },
+        }
+    }
+    let mut terms: Vec<Term> = like.into_iter().filter(|(_, n)| *n != 0.0).map(|(rest, n)| reduce_product(vec![Term::Number(n), rest])).collect();
+    terms.sort_by(compare);
+    if constant != 0.0 || terms.is_empty() {
+        terms.push(Term::Number(constant));
+    }
+    if terms.len() == 1 { terms.remove(0) } else { Term::Sum(terms) }
+}
+
+fn reduce_product(factors: Vec<Term>) -> Term {
+    //collect the exponents of equal bases, x * x ** 2 is x ** 3
+    let mut constant = 1.0;
+    let mut like: Vec<(Term, Vec<Term>)> = Vec::new();
+    let mut pending = factors;
+    while let Some(factor) = pending.pop() {
+        match factor {
+            Term::Product(inner) => pending.extend(inner),
+            Term::Number(n) => constant *= n,
+            factor => {
+                let (base, exponent) = match factor {
+                    Term::Power(base, exponent) => (*base, *exponent),
+                    factor => (factor, Term::Number(1.0)),
+                };
+                match like.iter_mut().find(|(other, _)| *other == base) {
+                    Some((_, exponents)) => exponents.push(exponent),
+                    None => like.push((base, vec![exponent])),
+                }
+            },
+        }
+    }
+    if constant == 0.0 {
+        return Term::Number(0.0);
+    }
+    let (mut factors, mut spread) = (Vec::new(), false);
+    for (base, exponents) in like {
+        match reduce_power(base, reduce_sum(exponents)) {
+            Term::Number(n) => constant *= n,
+            Term::Product(inner) => {
+                factors.extend(inner);
+                spread = true;
+            },
+            factor => factors.push(factor),
+        }
+    }
+    //a power of a product was spread over its factors, which may meet the others, (2x) ** 2 * x
+    if spread {
+        factors.push(Term::Number(constant));
+        return reduce_product(factors);
+    }
+    //a number times a sum is spread over it so its terms can meet their like terms, 2(x + 1) is 2x + 2
+    if let [Term::Sum(terms)] = factors.as_slice() {
+        if constant != 1.0 {
+            return reduce_sum(terms.iter().map(|term| reduce_product(vec![Term::Number(constant), term.clone()])).collect());
+        }
+    }
+    //names and their powers come before calls, 2 * x * cos(x)
+    factors.sort_by_key(|factor| (!matches!(factor, Term::Symbol(_) | Term::Power(_, _)), key(factor)));
+    if constant != 1.0 || factors.is_empty() {
+        factors.insert(0, Term::Number(constant));
+    }
+    if factors.len() == 1 { factors.remove(0) } else { Term::Product(factors) }
+}
+
+fn reduce_power(base: Term, exponent: Term) -> Term {
+    if exponent == Term::Number(0.0) || base == Term::Number(1.0) {
+        return Term::Number(1.0);
+    }
+    if exponent == Term::Number(1.0) {
+        return base;
+    }
+    match (base, exponent) {
+        (Term::Number(b), Term::Number(e)) if b.powf(e).is_finite() => Term::Number(b.powf(e)),
+        //(x ** a) ** n is x ** (a * n) as long as n is a whole number
+        (Term::Power(base, inner), Term::Number(e)) if e.fract() == 0.0 => reduce_power(*base, reduce_product(vec![*inner, Term::Number(e)])),
+        (Term::Product(factors), Term::Number(e)) if e.fract() == 0.0 => {
+            reduce_product(factors.into_iter().map(|factor| reduce_power(factor, Term::Number(e))).collect())
+        },
+        (base, exponent) => Term::Power(Box::new(base), Box::new(exponent)),
+    }
+}
+
+fn contains(term: &Term, variable: &str) -> bool {
+    match term {
+        Term::Number(_) => false,
+        Term::Symbol(name) => name == variable,
+        Term::Sum(terms) | Term::Product(terms) | Term::Call(_, terms) => terms.iter().any(|term| contains(term, variable)),
+        Term::Power(base, exponent) => contains(base, variable) || contains(exponent, variable),
+    }
+}
+
+fn call(name: &str, argument: Term) -> Term {
+    Term::Call(name.to_string(), vec![argument])
+}
+
+fn power(base: Term, exponent: f64) -> Term {
+    Term::Power(Box::new(base), Box::new(Term::Number(exponent)))
+}
+
+/**
+ The derivative of a term, not simplified yet.
+ */
+fn derivative(term: &Term, variable: &str) -> Result<Term, String> {
+    if !contains(term, variable) {
+        return Ok(Term::Number(0.0));
+    }
+    Ok(match term {
+        Term::Number(_) => Term::Number(0.0),
+        Term::Symbol(_) => Term::Number(1.0),
+        Term::Sum(terms) => Term::Sum(terms.iter().map(|term| derivative(term, variable)).collect::<Result<Vec<Term>, String>>()?),
+        //product rule, every factor is differentiated once while the others stay
+        Term::Product(factors) => {
+            let mut terms = Vec::new();
+            for i in 0..factors.len() {
+                let mut product = factors.clone();
+                product[i] = derivative(&factors[i], variable)?;
+                terms.push(Term::Product(product));
+            }
+            Term::Sum(terms)
+        },
+        Term::Power(base, exponent) => {
+            let (b, e) = (base.as_ref().clone(), exponent.as_ref().clone());
+            if !contains(exponent, variable) {
+                //x ** n is n * x ** (n - 1)
+                let lower = Term::Power(Box::new(b.clone()), Box::new(Term::Sum(vec![e.clone(), Term::Number(-1.0)])));
+                Term::Product(vec![e, lower, derivative(base, variable)?])
+            } else if !contains(base, variable) {
+                //a ** x is a ** x * ln(a)
+                Term::Product(vec![term.clone(), call("ln", b), derivative(exponent, variable)?])
+            } else {
+                //x ** x is x ** x * (ln(x) * e' + e * x' / x)
+                let inner = Term::Sum(vec![
+                    Term::Product(vec![derivative(exponent, variable)?, call("ln", b.clone())]),
+                    Term::Product(vec![e, derivative(base, variable)?, power(b, -1.0)]),
+                ]);
+                Term::Product(vec![term.clone(), inner])
+            }
+        },
+        Term::Call(name, arguments) => {
+            let argument = match arguments.as_slice() {
+                [argument] if ELEMENTARY.contains(&name.as_str()) => argument.clone(),
+                _ => return Err(format!("Can not differentiate {}", name)),
+            };
+            //chain rule, the outer derivative times the derivative of the argument
+            let outer = match name.as_str() {
+                "sin" => call("cos", argument.clone()),
+                "cos" => Term::Product(vec![Term::Number(-1.0), call("sin", argument.clone())]),
+                "tan" => power(call("cos", argument.clone()), -2.0),
+                "exp" => term.clone(),
+                "ln" => power(argument.clone(), -1.0),
+                _ => Term::Product(vec![Term::Number(0.5), power(term.clone(), -1.0)]),
+            };
+            Term::Product(vec![outer, derivative(&argument, variable)?])
+        },
+    })
+}
+
+fn binary(left: EnumExpression, right: EnumExpression, operator: &str) -> EnumExpression {
+    EnumExpression::Binary(BinaryExpression::new(Box::new(left), Box::new(right), operator))
+}
+
+fn negate(expression: EnumExpression) -> EnumExpression {
+    EnumExpression::Unary(UnaryExpression::new(Box::new(expression), "-"))
+}
+
+fn number(n: f64) -> EnumExpression {
+    let token = if n.fract() == 0.0 && n.abs() < (1u64 << 53) as f64 { Token::Integer(n.abs() as i64) } else { Token::Float(n.abs()) };
+    let expression = EnumExpression::Constant(ConstExpression::new(token));
+    if n < 0.0 { negate(expression) } else { expression }
+}
+
+/**
+ Multiplies the expressions together, 1 when there are none.
+ */
+fn multiply(factors: Vec<EnumExpression>) -> EnumExpression {
+    factors.into_iter().reduce(|left, right| binary(left, right, "*")).unwrap_or_else(|| number(1.0))
+}
+
+/**
+ Turns a term back into an expression tree.
+ * Negative exponents become divisions and simple fractional coefficients are written as a quotient, x / 2 rather than 0.5 * x
+ */
+fn to_expression(term: &Term) -> EnumExpression {
+    match term {
+        Term::Number(n) => number(*n),
+        Term::Symbol(name) => EnumExpression::Variable(VariableExpression::new(name.clone())),
+        Term::Call(name, arguments) => EnumExpression::Call(CallExpression::new(name.clone(), arguments.iter().map(to_expression).collect())),
+        Term::Sum(terms) => {
+            let mut expression = to_expression(&terms[0]);
+            for term in &terms[1..] {
+                expression = match coefficient(term.clone()) {
+                    (n, rest) if n < 0.0 => binary(expression, to_expression(&rest.map_or(Term::Number(-n), |rest| Term::Product(vec![Term::Number(-n), rest]))), "-"),
+                    _ => binary(expression, to_expression(term), "+"),
+                };
+            }
+            expression
+        },
+        Term::Product(_) | Term::Power(_, _) => {
+            let (n, rest) = coefficient(term.clone());
+            let factors = match rest {
+                Some(Term::Product(factors)) => factors,
+                Some(factor) => vec![factor],
+                None => Vec::new(),
+            };
+            let (mut numerator, mut denominator) = (Vec::new(), Vec::new());
+            match number::rational(n.abs(), 1000, 1e-12) {
+                Some((p, q)) if (p as f64 / q as f64 - n.abs()).abs() <= 1e-12 => {
+                    if p != 1 {
+                        numerator.push(number(p as f64));
+                    }
+                    if q != 1 {
+                        denominator.push(number(q as f64));
+                    }
+                },
+                _ => numerator.push(number(n.abs())),
+            }
+            for factor in factors {
+                match factor {
+                    Term::Power(base, exponent) => match *exponent {
+                        Term::Number(e) if e < 0.0 => denominator.push(to_expression(&reduce_power(*base, Term::Number(-e)))),
+                        exponent => numerator.push(binary(to_expression(&base), to_expression(&exponent), "**")),
+                    },
+                    factor => numerator.push(to_expression(&factor)),
+                }
+            }
+            //the sign goes on the first factor, -2 * x / y
+            if n < 0.0 {
+                let first = if numerator.is_empty() { number(1.0) } else { numerator.remove(0) };
+                numerator.insert(0, negate(first));
+            }
+            if denominator.is_empty() { multiply(numerator) } else { binary(multiply(numerator), multiply(denominator), "/") }
+        },
+    }
+}
+
+/**
+ How loosely an expression binds when it is written out, the same levels the parser uses.
+ */
+fn level(expression: &EnumExpression) -> usize {
+    match expression {
+        EnumExpression::Constant(constant) => match constant.constant {
+            Constant::Integer(i) if i < 0 => 2,
+            Constant::Float(f) if f < 0.0 => 2,
+            _ => 0,
+        },
+        EnumExpression::Unary(_) => 2,
+        EnumExpression::Binary(binary) => precedence(binary.operator()),
+        EnumExpression::Conditional(_) => precedence("?"),
+        EnumExpression::Lambda(_) => precedence("->"),
+        _ => 0,
+    }
+}
+
+/**
+ Writes the expression, wrapped in parenthesis when it binds looser than the limit allows.
+ */
+fn operand(f: &mut fmt::Formatter, expression: &EnumExpression, limit: usize) -> fmt::Result {
+    if level(expression) > limit {
+        write!(f, "({})", expression)
+    } else {
+        write!(f, "{}", expression)
+    }
+}
+
+fn list(f: &mut fmt::Formatter, items: &[EnumExpression]) -> fmt::Result {
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}", item)?;
+    }
+    Ok(())
+}
+
+/**
+ Writes an expression the way it would be typed, so it can be parsed again.
+ */
+impl fmt::Display for EnumExpression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EnumExpression::Constant(constant) => write!(f, "{}", constant.constant.describe(&FormatOptions::default()).1),
+            EnumExpression::Variable(variable) => write!(f, "{}", variable.name),
+            EnumExpression::Call(call) => {
+                write!(f, "{}(", call.name)?;
+                list(f, &call.arguments)?;
+                write!(f, ")")
+            },
+            EnumExpression::List(items) => {
+                write!(f, "[")?;
+                list(f, &items.items)?;
+                write!(f, "]")
+            },
+            EnumExpression::Unary(UnaryExpression::Percent(percent)) => {
+                operand(f, &percent.expression, 0)?;
+                write!(f, "%")
+            },
+            EnumExpression::Unary(unary) => {
+                write!(f, "{}", unary.operator())?;
+                operand(f, unary.operand(), 0)
+            },
+            EnumExpression::Binary(binary) => {
+                let (left, right) = binary.operands();
+                match binary.operator() {
+                    "√" => {
+                        //a square root leaves its index out
+                        if !matches!(right, EnumExpression::Constant(ConstExpression { constant: Constant::Integer(2) })) {
+                            operand(f, right, 0)?;
+                            write!(f, " ")?;
+                        }
+                        write!(f, "√")?;
+                        operand(f, left, 0)
+                    },
+                    "**" => {
+                        operand(f, left, 0)?;
+                        write!(f, " ** ")?;
+                        operand(f, right, 0)
+                    },
+                    operator => {
+                        //operators of one level go from left to right, so only the right side needs parenthesis at the same level
+                        let limit = level(self);
+                        operand(f, left, limit)?;
+                        write!(f, " {} ", operator)?;
+                        operand(f, right, limit - 1)
+                    },
+                }
+            },
+            EnumExpression::Conditional(conditional) => {
+                //the condition and consequent can hold anything up to ||, the alternative may be another conditional
+                operand(f, &conditional.condition, precedence("||"))?;
+                write!(f, " ? ")?;
+                operand(f, &conditional.consequent, precedence("||"))?;
+                write!(f, " : ")?;
+                operand(f, &conditional.alternative, precedence("?"))
+            },
+            EnumExpression::Modular(modular) => {
+                write!(f, "mod ")?;
+                operand(f, &modular.modulus, 0)?;
+                write!(f, " {{ {} }}", modular.expression)
+            },
+            EnumExpression::Lambda(lambda) => {
+                if lambda.parameters.len() == 1 {
+                    write!(f, "{} -> {}", lambda.parameters[0], lambda.body)
+                } else {
+                    write!(f, "({}) -> {}", lambda.parameters.join(", "), lambda.body)
+                }
+            },
+        }
+    }
+}
@@ -52,8 +52,18 @@ pub enum Token {
     Octal(i64),
     #[token("**")]
     Exponent,
-    #[token("//")]
+    #[token("√")]
     Root,
+    #[token("//")]
+    FloorDivide,
+    #[token("/.")]
+    TrueDivide,
+    #[token("div")]
+    EuclideanDivide,
+    #[token("mod")]
+    EuclideanModulo,
+    #[token("rem")]
+    Remainder,
     #[token("<<")]
     LeftShift,
     #[token(">>")]
@@ -62,6 +72,26 @@ pub enum Token {
     And,
     #[token("|")]
     Or,
+    #[token("&&")]
+    LogicalAnd,
+    #[token("||")]
+    LogicalOr,
+    #[token("==")]
+    Equal,
+    #[token("!=")]
+    NotEqual,
+    #[token("<")]
+    Less,
+    #[token("<=")]
+    LessEqual,
+    #[token(">")]
+    Greater,
+    #[token(">=")]
+    GreaterEqual,
+    #[token("?")]
+    Question,
+    #[token(":")]
+    Colon,
     #[token("^")]
     Xor,
     #[token("!")]
@@ -76,10 +106,30 @@ pub enum Token {
     Divide,
     #[token("%")]
     Modulo,
+    //a % that no value follows, told apart from a remainder by the parser rather than the lexer
+    Percent,
     #[token("(")]
     LeftParenthesis,
     #[token(")")]
     RightParenthesis,
+    #[token(",")]
+    Comma,
+    #[token("->")]
+    Arrow,
+    #[token("=")]
+    Assign,
+    #[token("{")]
+    LeftBrace,
+    #[token("}")]
+    RightBrace,
+    #[token("[")]
+    LeftBracket,
+    #[token("]")]
+    RightBracket,
+    #[token("..")]
+    Range,
+    #[regex(r"[a-zA-Z_][a-zA-Z0-9_]*", |lex| lex.slice().to_string())]
+    Identifier(String),
 }
 
 impl Token {
@@ -93,4 +143,16 @@ impl Token {
             _ => false,
         }
     }
+    /**
+     Whether this token can begin a value, used to spot implicit multiplication.
+     */
+    pub fn starts_operand(&self) -> bool {
+        self.is_number() || matches!(self, Token::LeftParenthesis | Token::Identifier(_))
+    }
+    /**
+     Whether a minus after this token subtracts rather than negates.
+     */
+    pub fn ends_operand(&self) -> bool {
+        self.is_number() || matches!(self, Token::RightParenthesis | Token::RightBrace | Token::RightBracket | Token::Identifier(_) | Token::Percent)
+    }
 }
\ No newline at end of file
@@ -0,0 +1,115 @@
+use std::{fs, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{logger::logger, parser::{calculate::Implicit, constants::Target, context::AngleUnit, format::FormatOptions}};
+
+/**
+ The version of the settings file, a change to its layout counts it up.
+ */
+pub const VERSION: u64 = 1;
+
+/**
+ What the calculator starts with, a command that is handed a target, an implicit multiplication or format options uses those instead.
+ * The word size, signedness and decimal precision are the width, signed and decimals of the format options
+ */
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Settings {
+    pub version: u64,
+    pub target: Target,
+    pub angle: AngleUnit,
+    pub implicit: Implicit,
+    pub format: FormatOptions,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: VERSION,
+            target: Target::Integer,
+            angle: AngleUnit::default(),
+            implicit: Implicit::default(),
+            format: FormatOptions::default(),
+        }
+    }
+}
+
+/**
+ The settings along with the JSON file they are kept in.
+ */
+pub struct Store {
+    path: PathBuf,
+    settings: Settings,
+}
+
+impl Store {
+    /**
+     Reads the settings file, the defaults are used when there is none or it can not be read.
+     * A file of an older version is migrated and written back
+     */
+    pub fn open(path: PathBuf) -> Self {
+        let settings = match fs::read_to_string(&path) {
+            Ok(contents) => match serde_json::from_str::<Value>(&contents).map_err(|err| err.to_string()).and_then(migrate) {
+                Ok(settings) => settings,
+                Err(err) => {
+                    logger::error!("Using the default settings, {} can not be read: {}", path.display(), err);
+                    return Self { path, settings: Settings::default() };
+                },
+            },
+            Err(_) => Settings::default(),
+        };
+        let store = Self { path, settings };
+        if let Err(err) = store.save() {
+            logger::error!("{}", err);
+        }
+        store
+    }
+
+    pub fn get(&self) -> &Settings {
+        &self.settings
+    }
+
+    /**
+     Replaces the settings and writes them to the file.
+     */
+    pub fn set(&mut self, settings: Settings) -> Result<(), String> {
+        self.settings = Settings { version: VERSION, ..settings };
+        self.save()
+    }
+
+    fn save(&self) -> Result<(), String> {
+        if let Some(directory) = self.path.parent() {
+            fs::create_dir_all(directory).map_err(|err| format!("Could not create {}: {}", directory.display(), err))?;
+        }
+        let contents = serde_json::to_string_pretty(&self.settings).map_err(|err| err.to_string())?;
+        fs::write(&self.path, contents).map_err(|err| format!("Could not write {}: {}", self.path.display(), err))
+    }
+}
+
+/**
+ Brings the settings of any older version up to the current one.
+ * Every field that is still known is kept, a field that no longer fits is set back to its default and fields that are gone are dropped
+ */
+fn migrate(value: Value) -> Result<Settings, String> {
+    let fields = match value {
+        Value::Object(fields) => fields,
+        _ => return Err("The settings have to be an object".to_string()),
+    };
+    let version = fields.get("version").and_then(Value::as_u64).unwrap_or(0);
+    if version > VERSION {
+        return Err(format!("The settings are of version {} but only versions up to {} can be read", version, VERSION));
+    }
+    let mut settings = serde_json::to_value(Settings::default()).map_err(|err| err.to_string())?;
+    for (key, field) in fields {
+        if key == "version" || settings.get(&key).is_none() {
+            continue;
+        }
+        let previous = std::mem::replace(&mut settings[&key], field);
+        if serde_json::from_value::<Settings>(settings.clone()).is_err() {
+            logger::error!("The setting {} is not valid and is set back to its default", key);
+            settings[&key] = previous;
+        }
+    }
+    serde_json::from_value(settings).map_err(|err| err.to_string())
+}
@@ -0,0 +1,90 @@
+use std::{fs, io::Write, path::{Path, PathBuf}, process::{Command, Stdio}};
+
+/**
+ A settings file of its own for every test, with the given contents or none at all.
+ */
+fn settings(name: &str, contents: Option<&str>) -> PathBuf {
+  let path = std::env::temp_dir().join(format!("calcit-cli-{}-{}", name, std::process::id())).join("settings.json");
+  let _ = fs::remove_file(&path);
+  if let Some(contents) = contents {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, contents).unwrap();
+  }
+  path
+}
+
+/**
+ Runs calcit-cli and gives its stdout, stderr and exit code.
+ */
+fn cli(settings: &Path, arguments: &[&str], stdin: &str) -> (String, String, Option<i32>) {
+  let mut child = Command::new(env!("CARGO_BIN_EXE_calcit-cli"))
+    .args(arguments)
+    .env("CALCIT_SETTINGS", settings)
+    .stdin(Stdio::piped())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .spawn()
+    .unwrap();
+  child.stdin.take().unwrap().write_all(stdin.as_bytes()).unwrap();
+  let output = child.wait_with_output().unwrap();
+  (String::from_utf8(output.stdout).unwrap(), String::from_utf8(output.stderr).unwrap(), output.status.code())
+}
+
+#[test]
+fn test_options() {
+  let path = settings("options", None);
+  //a negative number is an expression, anything else with a dash an option
+  assert_eq!(cli(&path, &["-5"], ""), ("-5\n".to_string(), String::new(), Some(0)));
+  assert_eq!(cli(&path, &["-(2) * 3"], "").0, "-6\n");
+  let (stdout, stderr, code) = cli(&path, &["-x"], "");
+  assert_eq!((stdout.as_str(), code), ("", Some(2)));
+  assert!(stderr.starts_with("calcit-cli: Unknown option -x\n"));
+  assert_eq!(cli(&path, &["--int", "--radix", "1"], "").2, Some(2));
+  assert_eq!(cli(&path, &["--mod", "7", "3 * 5"], "").0, "1\n");
+  assert!(cli(&path, &["--mod"], "").1.starts_with("calcit-cli: --mod needs a modulus\n"));
+  assert_eq!(cli(&path, &["--mod", "x", "1"], "").2, Some(2));
+  assert_eq!(cli(&path, &["--implicit", "strict", "x = 3; 6/2x"], "").0, "3\n9\n");
+  assert_eq!(cli(&path, &["--implicit", "loose", "1"], "").2, Some(2));
+  //everything after -- is an expression
+  assert_eq!(cli(&path, &["--", "-x"], ""), (String::new(), "{\"line\":1,\"input\":\"-x\",\"error\":\"Unknown variable: x\"}\n".to_string(), Some(1)));
+  assert_eq!(cli(&path, &["--", "--hex"], "").2, Some(1));
+  assert!(cli(&path, &["--help"], "").0.starts_with("Usage: calcit-cli"));
+  let _ = fs::remove_dir_all(path.parent().unwrap());
+}
+
+#[test]
+fn test_failures() {
+  let path = settings("failures", None);
+  //a failing line does not stop the ones after it
+  let (stdout, stderr, code) = cli(&path, &[], "1 + 1\n1 +\n2 * 3\n");
+  assert_eq!((stdout.as_str(), code), ("2\n6\n", Some(1)));
+  assert_eq!(stderr, "{\"line\":2,\"input\":\"1 +\",\"error\":\"Could not parse the expression\"}\n");
+  assert_eq!(cli(&path, &["x = 2", "x ** 10"], ""), ("2\n1024\n".to_string(), String::new(), Some(0)));
+  assert_eq!(cli(&path, &["1", "y"], "").1, "{\"line\":2,\"input\":\"y\",\"error\":\"Unknown variable: y\"}\n");
+  //an overflow is reported like any other error instead of taking calcit-cli down
+  for expression in ["9223372036854775807 + 1", "2 ** 63", "1 << 64"] {
+    let failure = format!("{{\"line\":1,\"input\":\"{}\",\"error\":\"The result overflows 64 bits\"}}\n", expression);
+    assert_eq!(cli(&path, &[expression], ""), (String::new(), failure, Some(1)), "{}", expression);
+  }
+  let _ = fs::remove_dir_all(path.parent().unwrap());
+}
+
+#[test]
+fn test_targets() {
+  let path = settings("targets", None);
+  //without a target a float stays a float
+  assert_eq!(cli(&path, &["sin(1)", "20% of 50", "[sin(1), 2]"], "").0, "0.8414709848078965\n10\n[0.8414709848078965, 2]\n");
+  assert_eq!(cli(&path, &["--hex", "255"], "").0, "0xff\n");
+  assert_eq!(cli(&path, &["--float", "7"], "").0, "7\n");
+  assert_eq!(cli(&path, &["--bool", "0", "2"], "").0, "false\ntrue\n");
+  let _ = fs::remove_dir_all(path.parent().unwrap());
+}
+
+#[test]
+fn test_settings() {
+  //the target, angle mode and implicit multiplication of the app are the defaults
+  let path = settings("settings", Some("{\"version\": 1, \"target\": \"Hexadecimal\", \"angle\": \"Degrees\", \"implicit\": \"Strict\"}"));
+  assert_eq!(cli(&path, &["255", "sin(90)", "x = 3; 6/2x"], "").0, "0xff\n1\n0x3\n0x9\n");
+  assert_eq!(cli(&path, &["--int", "--rad", "--implicit", "textbook", "255", "sin(0)", "x = 3; 6/2x"], "").0, "255\n0\n3\n1\n");
+  let _ = fs::remove_dir_all(path.parent().unwrap());
+}
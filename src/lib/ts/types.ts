@@ -4,6 +4,18 @@ export interface Constant {
     Binary?: string
     Hexadecimal?: string
     Octal?: string
+    Boolean?: string
+    Factors?: string
+    List?: string
+    Matrix?: string
+    Function?: string
+    Expression?: string
+}
+
+export interface Statement {
+    line: number
+    input: string
+    result: { Ok?: Constant, Err?: string }
 }
 
 export enum Target {
@@ -12,6 +24,93 @@ export enum Target {
     Binary = "Binary",
     Hexadecimal = "Hexadecimal",
     Octal = "Octal",
+    Boolean = "Boolean",
+}
+
+export enum Implicit {
+    Textbook = "Textbook",
+    Strict = "Strict",
+}
+
+export enum Notation {
+    Plain = "Plain",
+    Scientific = "Scientific",
+    Engineering = "Engineering",
+}
+
+export interface FormatOptions {
+    decimals?: number
+    significant?: number
+    notation?: Notation
+    fraction?: boolean
+    denominator?: number
+    tolerance?: number
+    grouping?: boolean
+    uppercase?: boolean
+    width?: number
+    signed?: boolean
+    prefix?: boolean
+}
+
+export enum AngleUnit {
+    Radians = "Radians",
+    Degrees = "Degrees",
+    Gradians = "Gradians",
+}
+
+export interface Settings {
+    version: number
+    target: Target
+    angle: AngleUnit
+    implicit: Implicit
+    format: FormatOptions
+}
+
+export interface Representations {
+    signed: string
+    unsigned: string
+    hexadecimal: string
+    octal: string
+    binary: string
+    float: string
+    ieee: string
+}
+
+export interface HistoryEntry {
+    id: number
+    expression: string
+    target: Target
+    result: Constant
+    timestamp: number
+}
+
+export interface HistoryPage {
+    entries: HistoryEntry[]
+    total: number
+}
+
+export interface ImportReport {
+    version: number
+    imported: number
+    duplicates: number
+    errors: { line: number, message: string }[]
+}
+
+export enum ExportFormat {
+    Csv = "Csv",
+    Markdown = "Markdown",
+    JsonLines = "JsonLines",
+    Html = "Html",
+}
+
+export interface Point {
+    x: number
+    y: { Ok?: number, Err?: string }
+}
+
+export interface Sample {
+    points: Point[]
+    breaks: number[]
 }
 
 export const evaluateConstant = (constant: Constant): string => {
@@ -20,5 +119,11 @@ export const evaluateConstant = (constant: Constant): string => {
     if (constant.Binary) return constant.Binary
     if (constant.Hexadecimal) return constant.Hexadecimal
     if (constant.Octal) return constant.Octal
+    if (constant.Boolean) return constant.Boolean
+    if (constant.Factors) return constant.Factors
+    if (constant.List) return constant.List
+    if (constant.Matrix) return constant.Matrix
+    if (constant.Function) return constant.Function
+    if (constant.Expression) return constant.Expression
     return ''
 }
\ No newline at end of file